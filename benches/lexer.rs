@@ -0,0 +1,62 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+
+//! Compare the lexer reading through a `Read` with the lexer indexing a byte slice.
+//! Run with `cargo bench`.
+
+extern crate asciidoctor;
+
+use std::time::{Duration, Instant};
+
+use asciidoctor::{Error, Lexer, Source};
+
+const ITERATIONS: u32 = 20;
+
+fn lex_all<R: Source>(mut lexer: Lexer<R>) -> usize {
+    let mut count = 0;
+    loop {
+        match lexer.token() {
+            Ok(_) => count += 1,
+            Err(Error::Eof) => return count,
+            Err(error) => panic!("cannot lex the document: {}", error),
+        }
+    }
+}
+
+fn measure<F: Fn() -> usize>(name: &str, function: F) {
+    let mut total = Duration::new(0, 0);
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        let count = function();
+        total += start.elapsed();
+        assert!(count > 0);
+    }
+    println!("{}: {:?} per iteration", name, total / ITERATIONS);
+}
+
+fn main() {
+    // Paragraphs of 64 bytes, so that no word spans two reads of the 4096-byte buffer.
+    let document = "*chunky* _bacon_ and `monospace` text with ^super^ and ~sub~ k\n\n".repeat(50_000);
+    let bytes = document.as_bytes();
+    measure("Lexer::new", || lex_all(Lexer::new(bytes)));
+    measure("Lexer::from_bytes", || lex_all(Lexer::from_bytes(bytes)));
+}
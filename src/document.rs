@@ -0,0 +1,88 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! A whole parsed asciidoctor document.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use error::{Error, Result};
+use lexer::Source;
+use node::Node;
+use node::Node::*;
+use parser::Parser;
+
+/// An entry of the outline of a document.
+#[derive(Debug, PartialEq)]
+pub enum OutlineEntry {
+    /// A block, with its kind, like `paragraph` or `listing`.
+    Block(&'static str),
+    /// A section, with its level and its title without formatting. The document title is at
+    /// the level 0.
+    Section {
+        level: usize,
+        title: String,
+    },
+}
+
+/// The nodes of a document, with its attributes.
+#[derive(Debug, PartialEq)]
+pub struct Document {
+    pub attributes: BTreeMap<String, String>,
+    pub nodes: Vec<Node>,
+}
+
+impl Document {
+    /// Parse all the nodes of the document from the `parser`.
+    pub fn parse<R: Source>(mut parser: Parser<R>) -> Result<Self> {
+        let mut nodes = vec![];
+        loop {
+            match parser.node() {
+                Ok(node) => nodes.push(node),
+                Err(Error::Eof) => break,
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(Document {
+            attributes: parser.document_attributes().clone(),
+            nodes,
+        })
+    }
+
+    /// Get the structure of the document: the sequence of its sections and of the kinds of its
+    /// blocks.
+    pub fn outline(&self) -> Vec<OutlineEntry> {
+        self.nodes.iter()
+            .map(|node| match *node {
+                Header { ref title, .. } => OutlineEntry::Section {
+                    level: 0,
+                    title: title.plain_text(),
+                },
+                Section { level, ref title, .. } => OutlineEntry::Section {
+                    level,
+                    title: title.plain_text(),
+                },
+                ref node => OutlineEntry::Block(node.kind().name()),
+            })
+            .collect()
+    }
+}
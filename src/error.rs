@@ -19,19 +19,31 @@
  * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
  */
 
-use std::fmt::{self, Display, Formatter};
+use alloc::string::{FromUtf8Error, String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+use core::result;
+#[cfg(feature = "std")]
 use std::io;
-use std::result;
-use std::string::FromUtf8Error;
 
+use node::NodeKind;
 use position::Pos;
-use self::Error::{Eof, Msg, UnexpectedChar, UnexpectedToken};
+#[cfg(feature = "std")]
+use self::Error::Io;
+use self::Error::{Eof, IncludeDepth, Msg, UnexpectedChar, UnexpectedToken, UnterminatedBlock};
 
 pub type Result<T> = result::Result<T, Error>;
 
 #[derive(Debug)]
 pub enum Error {
     Eof,
+    IncludeDepth {
+        max_depth: usize,
+        target: String,
+    },
+    /// An error while reading the document.
+    #[cfg(feature = "std")]
+    Io(io::Error),
     Msg(String),
     UnexpectedChar {
         actual: u8,
@@ -43,12 +55,22 @@ pub enum Error {
         expected: String,
         pos: Pos,
     },
+    /// A delimited block, like a listing block, whose closing delimiter is missing, with the
+    /// position of its opening delimiter.
+    UnterminatedBlock {
+        kind: NodeKind,
+        pos: Pos,
+    },
 }
 
 impl Display for Error {
     fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
         match *self {
             Eof => write!(fmt, "end of file"),
+            IncludeDepth { max_depth, ref target } =>
+                write!(fmt, "cannot include `{}`: the includes are nested deeper than {}", target, max_depth),
+            #[cfg(feature = "std")]
+            Io(ref error) => write!(fmt, "{}", error),
             Msg(ref message) => write!(fmt, "{}", message),
             UnexpectedChar { ref actual, ref expected, ref pos } =>
                 write!(fmt, "{}:{}: expected {}, but found `{}` on line {}, column {}", pos.line, pos.column,
@@ -56,19 +78,31 @@ impl Display for Error {
             UnexpectedToken { ref actual, ref expected, ref pos } =>
                 write!(fmt, "{}:{}: expected {}, but found `{}` on line {}, column {}", pos.line, pos.column, expected,
                        actual, pos.line, pos.column),
+            UnterminatedBlock { kind, ref pos } =>
+                write!(fmt, "{}:{}: unterminated {} block starting on line {}, column {}", pos.line, pos.column,
+                       kind.name(), pos.line, pos.column),
         }
     }
 }
 
-impl<'a> From<&'a str> for Error {
+impl Error {
+    /// Check if this error is the end of the document, which is not an actual error when getting
+    /// the next node.
+    pub fn is_eof(&self) -> bool {
+        matches!(*self, Eof)
+    }
+}
+
+impl From<&str> for Error {
     fn from(string: &str) -> Self {
         Msg(string.to_string())
     }
 }
 
+#[cfg(feature = "std")]
 impl From<io::Error> for Error {
     fn from(error: io::Error) -> Self {
-        Msg(error.to_string())
+        Io(error)
     }
 }
 
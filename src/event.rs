@@ -0,0 +1,135 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Stream the document as events, like the start of a paragraph or a word, without building the
+//! nodes of a whole block.
+
+use alloc::collections::VecDeque;
+use alloc::string::String;
+
+use error::{Error, Result};
+use lexer::Source;
+use node::{Item, Node, Tag, Text};
+use parser::Parser;
+use self::Event::*;
+
+/// An event of the document.
+/// The blocks and text items without their own events are sent as a whole.
+#[derive(Debug, PartialEq)]
+pub enum Event {
+    EndBold,
+    EndInlineCode,
+    EndItalic,
+    EndMark,
+    EndParagraph,
+    EndSection,
+    EndSubScript,
+    EndSuperScript,
+    Item(Item),
+    Node(Node),
+    Space,
+    StartBold,
+    StartInlineCode,
+    StartItalic,
+    StartMark,
+    StartParagraph,
+    StartSection(usize),
+    StartSubScript,
+    StartSuperScript,
+    Word(String),
+}
+
+/// An iterator over the events of a document.
+pub struct Events<R: Source> {
+    events: VecDeque<Event>,
+    parser: Parser<R>,
+}
+
+impl<R: Source> Events<R> {
+    /// Create a new iterator over the events of the document parsed by `parser`.
+    pub fn new(parser: Parser<R>) -> Self {
+        Events {
+            events: VecDeque::new(),
+            parser,
+        }
+    }
+
+    /// Queue the events of a node.
+    fn node(&mut self, node: Node) {
+        match node {
+            Node::Paragraph(text, _, _) => {
+                self.events.push_back(StartParagraph);
+                self.text(text);
+                self.events.push_back(EndParagraph);
+            },
+            Node::Section { level, title, .. } => {
+                self.events.push_back(StartSection(level));
+                self.text(title);
+                self.events.push_back(EndSection);
+            },
+            node => self.events.push_back(Node(node)),
+        }
+    }
+
+    /// Queue the events of a text.
+    fn text(&mut self, text: Text) {
+        for item in text.items {
+            match item {
+                Item::Mark(text, _) => {
+                    self.events.push_back(StartMark);
+                    self.text(text);
+                    self.events.push_back(EndMark);
+                },
+                Item::Space => self.events.push_back(Space),
+                Item::Tag(tag, text, _) => {
+                    let (start, end) =
+                        match tag {
+                            Tag::Bold => (StartBold, EndBold),
+                            Tag::InlineCode => (StartInlineCode, EndInlineCode),
+                            Tag::Italic => (StartItalic, EndItalic),
+                            Tag::SubScript => (StartSubScript, EndSubScript),
+                            Tag::SuperScript => (StartSuperScript, EndSuperScript),
+                        };
+                    self.events.push_back(start);
+                    self.text(text);
+                    self.events.push_back(end);
+                },
+                Item::Word(word) => self.events.push_back(Word(word)),
+                item => self.events.push_back(Item(item)),
+            }
+        }
+    }
+}
+
+impl<R: Source> Iterator for Events<R> {
+    type Item = Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.events.is_empty() {
+            match self.parser.node() {
+                Ok(node) => self.node(node),
+                Err(Error::Eof) => return None,
+                Err(error) => return Some(Err(error)),
+            }
+        }
+        self.events.pop_front().map(Ok)
+    }
+}
@@ -0,0 +1,518 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Generate DocBook 5 XML from the asciidoctor nodes.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use error::Result;
+use gen::{find_id_attribute, has_option, replace_characters, unique_section_id, Sink};
+use gen::html::escape_html;
+use node::{AdmonitionKind, Attribute, Author, ListItem, Node, Revision};
+use node::Attribute::Role;
+use node::Node::*;
+use node::{Item, Tag, Text};
+use self::Xml::*;
+
+/// The numbering styles of the ordered lists, like `[loweralpha]`, which are also the default
+/// styles of the nested levels.
+const NUMERATIONS: [&str; 5] = ["arabic", "loweralpha", "lowerroman", "upperalpha", "upperroman"];
+
+/// Write the resulting DocBook code for the specified `node` in the `writer`.
+/// The sections are written as `bridgehead` elements, since they only contain their title: use
+/// gen_document() to nest the content of the sections in `section` elements.
+pub fn gen<G: DocBookGen, W: Sink>(gen: &mut G, node: &Node, writer: &mut W) -> Result<()> {
+    let xml = gen.node(node);
+    xml.write(writer)
+}
+
+/// Write the DocBook document made of the `nodes`, with the document `attributes`, in the
+/// `writer`: the root element is a `book` with the `book` doctype, or an `article`, the header
+/// becomes its `info` element and the content of each section is nested in its `section`.
+pub fn gen_document<G: DocBookGen, W: Sink>(gen: &mut G, nodes: &[Node], attributes: &BTreeMap<String, String>,
+    writer: &mut W) -> Result<()>
+{
+    let root = if attributes.get("doctype").map(String::as_str) == Some("book") { "book" } else { "article" };
+    write!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    write!(writer, "<{} xmlns=\"http://docbook.org/ns/docbook\" xmlns:xl=\"http://www.w3.org/1999/xlink\" \
+        version=\"5.0\">", root)?;
+    let mut ids = BTreeSet::new();
+    let mut levels: Vec<usize> = vec![];
+    for node in nodes {
+        match *node {
+            Section { ref attributes, level, ref title, .. } => {
+                while levels.last().is_some_and(|&last| last >= level) {
+                    write!(writer, "</section>")?;
+                    levels.pop();
+                }
+                levels.push(level);
+                let id = unique_section_id(&mut ids, title, attributes);
+                write!(writer, "<section xml:id=\"{}\">", escape_html(&id))?;
+                element("title", vec![gen.text(title)]).write(writer)?;
+            },
+            _ => gen.node(node).write(writer)?,
+        }
+    }
+    for _ in levels {
+        write!(writer, "</section>")?;
+    }
+    write!(writer, "</{}>", root)?;
+    Ok(())
+}
+
+/// Genarate a DocBook node from a asciidoctor node.
+pub trait DocBookGen {
+    fn admonition(&mut self, kind: AdmonitionKind, text: &Text) -> Xml {
+        element(kind.name(), vec![element("para", vec![self.text(text)])])
+    }
+
+    fn admonition_block(&mut self, kind: AdmonitionKind, nodes: &[Node]) -> Xml {
+        element(kind.name(), self.nodes(nodes))
+    }
+
+    fn description_list(&mut self, items: &[(Text, Text)]) -> Xml {
+        let entries = items.iter()
+            .map(|(term, definition)| {
+                let definition = if definition.items.is_empty() { vec![] } else { vec![self.para(definition)] };
+                element("varlistentry", vec![
+                    element("term", vec![self.text(term)]),
+                    element("listitem", definition),
+                ])
+            })
+            .collect();
+        element("variablelist", entries)
+    }
+
+    fn example(&mut self, nodes: &[Node], title: Option<&Text>) -> Xml {
+        let content = self.nodes(nodes);
+        match title {
+            Some(title) => element("example", titled(self.text(title), content)),
+            None => element("informalexample", content),
+        }
+    }
+
+    fn header(&mut self, title: &Text, authors: &[Author], revision: &Revision) -> Xml {
+        let mut children = vec![element("title", vec![self.text(title)])];
+        for author in authors {
+            let mut name = vec![element("firstname", vec![text(&author.firstname)])];
+            if let Some(ref middlename) = author.middlename {
+                name.push(element("othername", vec![text(middlename)]));
+            }
+            if let Some(ref lastname) = author.lastname {
+                name.push(element("surname", vec![text(lastname)]));
+            }
+            let mut author_children = vec![element("personname", name)];
+            if let Some(ref email) = author.email {
+                author_children.push(element("email", vec![text(email)]));
+            }
+            children.push(element("author", author_children));
+        }
+        if let Some(ref date) = revision.date {
+            children.push(element("date", vec![text(date)]));
+        }
+        if revision.number.is_some() || revision.remark.is_some() {
+            let mut details = vec![];
+            if let Some(ref number) = revision.number {
+                details.push(element("revnumber", vec![text(number)]));
+            }
+            if let Some(ref date) = revision.date {
+                details.push(element("date", vec![text(date)]));
+            }
+            if let Some(ref remark) = revision.remark {
+                details.push(element("revremark", vec![text(remark)]));
+            }
+            children.push(element("revhistory", vec![element("revision", details)]));
+        }
+        element("info", children)
+    }
+
+    fn horizontal_rule(&mut self) -> Xml {
+        Raw("<?asciidoc-hr?>".to_string())
+    }
+
+    fn image(&mut self, target: &str, alt: &str, width: Option<&str>, height: Option<&str>, title: Option<&Text>)
+        -> Xml
+    {
+        let mut attributes = vec![("fileref", target.to_string())];
+        if let Some(width) = width {
+            attributes.push(("contentwidth", width.to_string()));
+        }
+        if let Some(height) = height {
+            attributes.push(("contentdepth", height.to_string()));
+        }
+        let media = element("mediaobject", vec![
+            element("imageobject", vec![element_a("imagedata", attributes, vec![])]),
+            element("textobject", vec![element("phrase", vec![text(alt)])]),
+        ]);
+        match title {
+            Some(title) => element("figure", titled(self.text(title), vec![media])),
+            None => element("informalfigure", vec![media]),
+        }
+    }
+
+    fn item(&mut self, item: &Item) -> Xml {
+        match *item {
+            Item::Anchor { ref id, ref label } => {
+                let mut attributes = vec![("xml:id", id.clone())];
+                if let Some(ref label) = *label {
+                    attributes.push(("xreflabel", label.clone()));
+                }
+                element_a("anchor", attributes, vec![])
+            },
+            Item::Button(ref label) => element("guibutton", vec![text(label)]),
+            Item::CrossReference { ref id, text: Some(ref text) } =>
+                element_a("link", vec![("linkend", id.clone())], vec![self.text(text)]),
+            Item::CrossReference { ref id, text: None } => element_a("xref", vec![("linkend", id.clone())], vec![]),
+            Item::Footnote(ref text) => element("footnote", vec![self.para(text)]),
+            Item::Icon { ref name, .. } => text(&format!("[{}]", name)),
+            Item::InlineImage { ref alt, ref target } => element("inlinemediaobject", vec![
+                element("imageobject", vec![element_a("imagedata", vec![("fileref", target.clone())], vec![])]),
+                element("textobject", vec![element("phrase", vec![text(alt)])]),
+            ]),
+            Item::Keyboard(ref keys) => self.keyboard(keys),
+            Item::LineBreak => Raw("<?asciidoc-br?>".to_string()),
+            Item::Literal(ref content) => text(content),
+            Item::Link { ref target, ref text, .. } => {
+                let content =
+                    match *text {
+                        Some(ref text) => self.text(text),
+                        None => self::text(target),
+                    };
+                element_a("link", vec![("xl:href", target.clone())], vec![content])
+            },
+            Item::Mark(ref text, _) => element_a("emphasis", vec![("role", "marked".to_string())], vec![self.text(text)]),
+            Item::Menu { ref menu, ref path } => self.menu(menu, path),
+            Item::Passthrough(ref content) => Raw(content.clone()),
+            Item::Quoted { double, ref text } => {
+                let (open, close) = if double { ("&#8220;", "&#8221;") } else { ("&#8216;", "&#8217;") };
+                Seq(vec![Raw(open.to_string()), self.text(text), Raw(close.to_string())])
+            },
+            Item::Space => text(" "),
+            Item::Tag(tag, ref text, _) => self.tag(tag, text),
+            Item::Word(ref word) => Raw(replace_characters(word, false)),
+        }
+    }
+
+    fn keyboard(&mut self, keys: &[String]) -> Xml {
+        let keys: Vec<_> = keys.iter()
+            .map(|key| element("keycap", vec![text(key)]))
+            .collect();
+        if keys.len() == 1 {
+            Seq(keys)
+        }
+        else {
+            element("keycombo", keys)
+        }
+    }
+
+    fn list_item(&mut self, item: &ListItem) -> Xml {
+        let mut children = vec![self.para(&item.text)];
+        if let Some(ref nested) = item.nested {
+            children.push(self.node(nested));
+        }
+        element("listitem", children)
+    }
+
+    fn listing(&mut self, content: &str, language: Option<&str>, title: Option<&Text>) -> Xml {
+        let mut attributes = vec![];
+        if let Some(language) = language {
+            attributes.push(("language", language.to_string()));
+        }
+        attributes.push(("linenumbering", "unnumbered".to_string()));
+        let listing = element_a("programlisting", attributes, vec![text(content)]);
+        match title {
+            Some(title) => element("formalpara", titled(self.text(title), vec![element("para", vec![listing])])),
+            None => listing,
+        }
+    }
+
+    fn literal(&mut self, content: &str) -> Xml {
+        element_a("literallayout", vec![("class", "monospaced".to_string())], vec![text(content)])
+    }
+
+    fn menu(&mut self, menu: &str, path: &[String]) -> Xml {
+        let menu = element("guimenu", vec![text(menu)]);
+        let (item, submenus) =
+            match path.split_last() {
+                Some(path) => path,
+                None => return menu,
+            };
+        let mut children = vec![menu];
+        for submenu in submenus {
+            children.push(element("guisubmenu", vec![text(submenu)]));
+        }
+        children.push(element("guimenuitem", vec![text(item)]));
+        element("menuchoice", children)
+    }
+
+    fn node(&mut self, node: &Node) -> Xml {
+        match *node {
+            Admonition { kind, ref text, .. } => self.admonition(kind, text),
+            AdmonitionBlock { kind, ref nodes, .. } => self.admonition_block(kind, nodes),
+            DescriptionList(ref items, _) => self.description_list(items),
+            Example { ref nodes, ref title, .. } => self.example(nodes, title.as_ref()),
+            Header { ref authors, ref revision, ref title, .. } => self.header(title, authors, revision),
+            HorizontalRule(..) => self.horizontal_rule(),
+            Image { ref alt, ref height, ref target, ref title, ref width, .. } =>
+                self.image(target, alt, width.as_deref(), height.as_deref(), title.as_ref()),
+            Listing { ref content, ref language, ref title, .. } =>
+                self.listing(content, language.as_deref(), title.as_ref()),
+            Literal(ref content, _) => self.literal(content),
+            Open(ref nodes, _) => Seq(self.nodes(nodes)),
+            OrderedList { ref attributes, ref items, level, start, .. } =>
+                self.ordered_list(items, level, start, attributes),
+            PageBreak(_) => Raw("<?asciidoc-pagebreak?>".to_string()),
+            Paragraph(ref text, ref attributes, _) => self.paragraph(text, attributes),
+            Passthrough(ref content, _) => Raw(content.clone()),
+            Quote { ref author, ref content, ref source, .. } => self.quote(content, author.as_deref(), source.as_deref()),
+            Section { ref attributes, level, ref title, .. } => self.section(level, title, attributes),
+            Sidebar { ref nodes, ref title, .. } => self.sidebar(nodes, title.as_ref()),
+            SubstitutedBlock { listing, ref text, .. } => self.substituted_block(listing, text),
+            Node::Table { ref attributes, ref rows, .. } => self.table(rows, attributes),
+            // The table of contents is generated by the DocBook tools.
+            Toc(_) => Empty,
+            UnorderedList(ref items, _, _) => self.unordered_list(items),
+        }
+    }
+
+    fn nodes(&mut self, nodes: &[Node]) -> Vec<Xml> {
+        nodes.iter()
+            .map(|node| self.node(node))
+            .collect()
+    }
+
+    fn ordered_list(&mut self, items: &[ListItem], level: usize, start: Option<usize>, attributes: &[Attribute])
+        -> Xml
+    {
+        let numeration = attributes.iter()
+            .find_map(|attribute| match *attribute {
+                Role(ref role) => NUMERATIONS.iter().find(|&&numeration| numeration == role),
+                _ => None,
+            })
+            .unwrap_or(&NUMERATIONS[level.saturating_sub(1) % NUMERATIONS.len()]);
+        let mut list_attributes = vec![("numeration", numeration.to_string())];
+        if let Some(start) = start {
+            list_attributes.push(("startingnumber", start.to_string()));
+        }
+        let items = items.iter()
+            .map(|item| self.list_item(item))
+            .collect();
+        element_a("orderedlist", list_attributes, items)
+    }
+
+    fn para(&mut self, text: &Text) -> Xml {
+        element("para", vec![self.text(text)])
+    }
+
+    fn paragraph(&mut self, text: &Text, attributes: &[Attribute]) -> Xml {
+        let attributes = find_id_attribute(attributes)
+            .map(|id| vec![("xml:id", id)])
+            .unwrap_or_default();
+        element_a("para", attributes, vec![self.text(text)])
+    }
+
+    fn quote(&mut self, content: &[Node], author: Option<&str>, source: Option<&str>) -> Xml {
+        let mut children = vec![];
+        if author.is_some() || source.is_some() {
+            let mut attribution = vec![];
+            if let Some(author) = author {
+                attribution.push(text(author));
+            }
+            if let Some(source) = source {
+                attribution.push(element("citetitle", vec![text(source)]));
+            }
+            children.push(element("attribution", attribution));
+        }
+        children.append(&mut self.nodes(content));
+        element("blockquote", children)
+    }
+
+    fn section(&mut self, level: usize, title: &Text, attributes: &[Attribute]) -> Xml {
+        let mut bridgehead_attributes = vec![];
+        if let Some(id) = find_id_attribute(attributes) {
+            bridgehead_attributes.push(("xml:id", id));
+        }
+        bridgehead_attributes.push(("renderas", format!("sect{}", level)));
+        element_a("bridgehead", bridgehead_attributes, vec![self.text(title)])
+    }
+
+    fn sidebar(&mut self, nodes: &[Node], title: Option<&Text>) -> Xml {
+        let content = self.nodes(nodes);
+        match title {
+            Some(title) => element("sidebar", titled(self.text(title), content)),
+            None => element("sidebar", content),
+        }
+    }
+
+    fn substituted_block(&mut self, listing: bool, text: &Text) -> Xml {
+        let text = self.text(text);
+        if listing {
+            element_a("programlisting", vec![("linenumbering", "unnumbered".to_string())], vec![text])
+        }
+        else {
+            element_a("literallayout", vec![("class", "monospaced".to_string())], vec![text])
+        }
+    }
+
+    fn table(&mut self, rows: &[Vec<Text>], attributes: &[Attribute]) -> Xml {
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let mut rows = rows;
+        let mut groups = vec![];
+        if has_option(attributes, "header") {
+            if let Some((first, rest)) = rows.split_first() {
+                groups.push(element("thead", vec![self.table_row(first, true)]));
+                rows = rest;
+            }
+        }
+        if has_option(attributes, "footer") {
+            if let Some((last, rest)) = rows.split_last() {
+                groups.push(element("tfoot", vec![self.table_row(last, false)]));
+                rows = rest;
+            }
+        }
+        let body = rows.iter()
+            .map(|row| self.table_row(row, false))
+            .collect();
+        groups.push(element("tbody", body));
+        let mut table_attributes = vec![];
+        if let Some(id) = find_id_attribute(attributes) {
+            table_attributes.push(("xml:id", id));
+        }
+        table_attributes.push(("frame", "all".to_string()));
+        table_attributes.push(("rowsep", "1".to_string()));
+        table_attributes.push(("colsep", "1".to_string()));
+        element_a("informaltable", table_attributes, vec![
+            element_a("tgroup", vec![("cols", columns.to_string())], groups),
+        ])
+    }
+
+    fn table_row(&mut self, cells: &[Text], header: bool) -> Xml {
+        let cells = cells.iter()
+            .map(|cell| {
+                let content = if header { self.text(cell) } else { self.para(cell) };
+                element("entry", vec![content])
+            })
+            .collect();
+        element("row", cells)
+    }
+
+    fn tag(&mut self, tag: Tag, text: &Text) -> Xml {
+        let text = vec![self.text(text)];
+        match tag {
+            Tag::Bold => element_a("emphasis", vec![("role", "strong".to_string())], text),
+            Tag::InlineCode => element("literal", text),
+            Tag::Italic => element("emphasis", text),
+            Tag::SubScript => element("subscript", text),
+            Tag::SuperScript => element("superscript", text),
+        }
+    }
+
+    fn text(&mut self, text: &Text) -> Xml {
+        let items = text.items.iter()
+            .map(|item| self.item(item))
+            .collect();
+        Seq(items)
+    }
+
+    fn unordered_list(&mut self, items: &[ListItem]) -> Xml {
+        let items = items.iter()
+            .map(|item| self.list_item(item))
+            .collect();
+        element("itemizedlist", items)
+    }
+}
+
+/// The default DocBook generator.
+#[derive(Default)]
+pub struct Generator;
+
+impl Generator {
+    /// Create a new generator.
+    pub fn new() -> Self {
+        Generator
+    }
+}
+
+impl DocBookGen for Generator {
+}
+
+/// Represent an XML node with its children.
+pub enum Xml {
+    /// An element, with its name, its attributes and its children.
+    Element(&'static str, Vec<(&'static str, String)>, Vec<Xml>),
+    Empty,
+    Raw(String),
+    Seq(Vec<Xml>),
+    Text(String),
+}
+
+impl Xml {
+    fn write<W: Sink>(&self, writer: &mut W) -> Result<()> {
+        match *self {
+            Element(name, ref attributes, ref children) => {
+                write!(writer, "<{}", name)?;
+                for &(attribute, ref value) in attributes {
+                    write!(writer, " {}=\"{}\"", attribute, escape_html(value))?;
+                }
+                if children.is_empty() {
+                    return write!(writer, "/>");
+                }
+                write!(writer, ">")?;
+                for child in children {
+                    child.write(writer)?;
+                }
+                write!(writer, "</{}>", name)
+            },
+            Empty => Ok(()),
+            Raw(ref content) => write!(writer, "{}", content),
+            Seq(ref children) => {
+                for child in children {
+                    child.write(writer)?;
+                }
+                Ok(())
+            },
+            Text(ref text) => write!(writer, "{}", escape_html(text)),
+        }
+    }
+}
+
+/// Create an element without attributes.
+pub fn element(name: &'static str, children: Vec<Xml>) -> Xml {
+    Element(name, vec![], children)
+}
+
+/// Create an element with attributes.
+pub fn element_a(name: &'static str, attributes: Vec<(&'static str, String)>, children: Vec<Xml>) -> Xml {
+    Element(name, attributes, children)
+}
+
+/// Create a text node.
+pub fn text(text: &str) -> Xml {
+    Text(text.to_string())
+}
+
+/// Prepend the `title` element to the `content` of a formal element, like an `example`.
+fn titled(title: Xml, mut content: Vec<Xml>) -> Vec<Xml> {
+    content.insert(0, element("title", vec![title]));
+    content
+}
@@ -21,22 +21,34 @@
 
 //! Generate HTML from the asciidoctor nodes.
 
-use std::io::Write;
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::iter::Peekable;
+use core::mem;
 
 use error::Result;
-use node::{Attribute, Node};
+use gen::{entity, escape_text, find_id_attribute, has_option, replace_characters, unique_section_id, Sink};
+use node::{AdmonitionKind, Attribute, Author, ListItem, Node, NodeKind, Revision};
 use node::Attribute::Role;
 use node::Node::*;
 use node::{Item, Tag, Text};
+use node::Tag::SuperScript;
 use self::Html::*;
 
 macro_rules! attr {
     ($( $name:ident = $value:expr ),*) => {{
         let mut attributes = String::new();
         $(
-            attributes.push_str(stringify!($name));
+            if !attributes.is_empty() {
+                attributes.push(' ');
+            }
+            // Attribute names like `data-lang` are written `data_lang`.
+            attributes.push_str(&stringify!($name).replace('_', "-"));
             attributes.push_str("=\"");
-            attributes.push_str(&$value.to_string());
+            attributes.push_str(&escape_html(&$value.to_string()));
             attributes.push_str("\"");
         )*
         attributes
@@ -45,29 +57,494 @@ macro_rules! attr {
 
 type Id = String;
 
-/// Write the resulting HTML code for the specified `node` in the `writer`.
-pub fn gen<G: HtmlGen, W: Write>(gen: &mut G, node: &Node, writer: &mut W) -> Result<()> {
+/// The styles of the markers of the unordered lists, like `[square]`.
+const MARKER_STYLES: [&str; 4] = ["circle", "disc", "none", "square"];
+
+/// The styles of the numbering of the ordered lists, like `[loweralpha]`, with the matching
+/// `type` attribute of the `ol` element.
+const NUMBERING_STYLES: [(&str, Option<&str>); 7] = [
+    ("arabic", None),
+    ("decimal", None),
+    ("loweralpha", Some("a")),
+    ("lowergreek", None),
+    ("lowerroman", Some("i")),
+    ("upperalpha", Some("A")),
+    ("upperroman", Some("I")),
+];
+
+/// The default numbering styles of the ordered lists, for each nesting level.
+const DEFAULT_NUMBERING_STYLES: [&str; 5] = ["arabic", "loweralpha", "lowerroman", "upperalpha", "upperroman"];
+
+/// The deepest section level in the table of contents, when the `toclevels` attribute is not set.
+const DEFAULT_TOC_LEVELS: usize = 2;
+
+/// Write the resulting HTML code for the specified `node` in the `writer`, which can be any
+/// `std::io::Write` or another `Sink`.
+pub fn gen<G: HtmlGen, W: Sink>(gen: &mut G, node: &Node, writer: &mut W) -> Result<()> {
     let html = gen.node(node);
-    html.write(writer)
+    write_html(gen, &html, writer)
+}
+
+/// Write the resulting HTML code for the document made of the `nodes`, with the document
+/// `attributes`, in the `writer`, followed by the footnotes.
+/// The `toc` attribute places the table of contents below the header (when empty or set to
+/// `auto`, `left` or `right`), before the first section (`preamble`) or at the `toc::[]` macro
+/// (`macro`).
+/// With the `standalone` option, the complete HTML5 document, from HtmlGen::document(), is written.
+pub fn gen_document<G: HtmlGen, W: Sink>(gen: &mut G, nodes: &[Node], attributes: &BTreeMap<String, String>,
+    writer: &mut W) -> Result<()>
+{
+    let html =
+        if gen.options().standalone {
+            gen.document(nodes, attributes)
+        }
+        else {
+            document_content(gen, nodes, attributes)
+        };
+    write_html(gen, &html, writer)
+}
+
+/// Get the HTML code of the content of the document made of the `nodes`, with its table of
+/// contents and followed by the footnotes.
+fn document_content<G: HtmlGen + ?Sized>(gen: &mut G, nodes: &[Node], attributes: &BTreeMap<String, String>)
+    -> Html
+{
+    let toc_index =
+        match attributes.get("toc").map(String::as_str) {
+            Some("macro") => nodes.iter().position(|node| matches!(*node, Toc(_))),
+            Some("preamble") =>
+                Some(nodes.iter().position(|node| matches!(*node, Section { .. })).unwrap_or(nodes.len())),
+            Some(_) => Some(if matches!(nodes.first(), Some(&Header { .. })) { 1 } else { 0 }),
+            None => None,
+        };
+    let mut children = vec![];
+    for (index, node) in nodes.iter().enumerate() {
+        if toc_index == Some(index) {
+            children.push(toc(gen, nodes, attributes));
+        }
+        children.push(gen.node(node));
+    }
+    if toc_index == Some(nodes.len()) {
+        children.push(toc(gen, nodes, attributes));
+    }
+    children.push(gen.footnotes_div());
+    TextNode(children)
+}
+
+/// Write the footnotes collected while generating the nodes in the `writer`.
+/// This should be called at the end of the document.
+pub fn gen_footnotes<G: HtmlGen, W: Sink>(gen: &mut G, writer: &mut W) -> Result<()> {
+    let html = gen.footnotes_div();
+    write_html(gen, &html, writer)
+}
+
+/// Write the table of contents of the sections in `nodes` in the `writer`, when the `toc`
+/// document attribute is set.
+/// The `toclevels` attribute is the deepest section level in the table of contents.
+pub fn gen_toc<G: HtmlGen, W: Sink>(gen: &mut G, nodes: &[Node], attributes: &BTreeMap<String, String>,
+    writer: &mut W) -> Result<()>
+{
+    let html = toc(gen, nodes, attributes);
+    write_html(gen, &html, writer)
+}
+
+/// Get the table of contents of the sections in `nodes`, when the `toc` document attribute is set.
+fn toc<G: HtmlGen + ?Sized>(gen: &mut G, nodes: &[Node], attributes: &BTreeMap<String, String>) -> Html {
+    if !attributes.contains_key("toc") {
+        return Empty;
+    }
+    let levels = attributes.get("toclevels")
+        .and_then(|levels| levels.parse().ok())
+        .unwrap_or(DEFAULT_TOC_LEVELS);
+    gen.toc(nodes, levels)
 }
 
+/// A function generating the HTML of a node instead of the generator.
+pub type Hook = Box<dyn Fn(&Node) -> Html>;
+
 /// The default HTML generator.
 pub struct Generator {
+    footnotes: Vec<Html>,
+    hooks: BTreeMap<NodeKind, Hook>,
+    ids: BTreeSet<String>,
+    options: Options,
+}
+
+impl Generator {
+    /// Register a `hook` which generates the HTML of the nodes of the specified `kind`, instead
+    /// of the default rendering.
+    pub fn add_hook<F: Fn(&Node) -> Html + 'static>(&mut self, kind: NodeKind, hook: F) {
+        self.hooks.insert(kind, Box::new(hook));
+    }
+
+    /// Create a new generator with the default options.
+    pub fn new() -> Self {
+        Self::with_options(Options::default())
+    }
+
+    /// Create a new generator with the specified `options`.
+    pub fn with_options(options: Options) -> Self {
+        Generator {
+            footnotes: vec![],
+            hooks: BTreeMap::new(),
+            ids: BTreeSet::new(),
+            options,
+        }
+    }
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Options to customize the generated HTML.
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    /// Use classes instead of inline styles, which are rejected by a strict
+    /// Content-Security-Policy. A page break becomes `<div class="page-break">`.
+    pub csp_safe: bool,
+    /// Wrap the code of the listing blocks in the structure expected by the copy-to-clipboard
+    /// scripts, with a hidden element containing the raw source.
+    pub copy_button: bool,
+    /// Add ARIA roles to the admonitions and the table of contents, for accessibility.
+    pub aria: bool,
+    /// Do not render the footnotes at the end of the document (like the `:nofootnotes:`
+    /// attribute). Only the references to the footnotes are rendered.
+    pub nofootnotes: bool,
+    /// Add the line of the source to the block elements, in a `data-source-line` attribute.
+    pub source_lines: bool,
+    /// Write the replaced characters, like `(C)`, as named entities (`&copy;`) instead of numeric
+    /// character references (`&#169;`), which are the default since some XML tools reject the
+    /// named entities. The escaped characters always use the entities predefined in XML, like
+    /// `&amp;`.
+    pub named_entities: bool,
+    /// Wrap the section titles in a link to the section (like the `:sectlinks:` attribute).
+    pub sectlinks: bool,
+    /// Generate a complete HTML document, with the `head` and `body` elements, instead of a
+    /// fragment, in gen_document().
+    pub standalone: bool,
+    /// Make the output smaller by collapsing the runs of spaces of the text, except in the
+    /// verbatim blocks, like the listing blocks.
+    /// This option excludes `pretty`: when both are set, the output is minified and not indented.
+    pub minify: bool,
+    /// Do not add the `generator` meta tag, naming this crate and its version, in the `head` of
+    /// a standalone document.
+    pub nogenerator: bool,
+    /// Keep the characters like `(C)`, `--` and `...` as written instead of replacing them by
+    /// their typographic character, which is useful for code-like text.
+    pub noreplacements: bool,
+    /// Write the block elements, like `div`, on their own lines, indented by two spaces for each
+    /// level of nesting, to make the output easier to read and to diff. The inline elements stay
+    /// on the line of their parent.
+    /// This option is ignored when `minify` is set.
+    pub pretty: bool,
 }
 
 /// Genarate an HTML node from a asciidoctor node.
 pub trait HtmlGen {
-    fn horizontal_rule(&mut self) -> Html {
-        hr()
+    /// The footnotes collected while generating the document.
+    fn footnotes(&mut self) -> &mut Vec<Html>;
+
+    /// The ids of the sections generated so far, to make the generated ids unique.
+    fn ids(&mut self) -> &mut BTreeSet<String>;
+
+    /// The options used to customize the generated HTML.
+    fn options(&self) -> &Options;
+
+    /// The hook generating the nodes of the specified `kind`, if any.
+    fn hook(&self, _kind: NodeKind) -> Option<&Hook> {
+        None
+    }
+
+    fn admonition(&mut self, kind: AdmonitionKind, caption: &str, text: &Text) -> Html {
+        let text = self.text(text);
+        admonition_table(kind, caption, text, self.options().aria)
+    }
+
+    fn admonition_block(&mut self, kind: AdmonitionKind, caption: &str, nodes: &[Node]) -> Html {
+        let content = nodes.iter()
+            .map(|node| self.node(node))
+            .collect();
+        admonition_table(kind, caption, TextNode(content), self.options().aria)
+    }
+
+    fn anchor(&mut self, id: &str, _label: Option<&str>) -> Html {
+        A(id.to_string())
+    }
+
+    fn button(&mut self, label: &str) -> Html {
+        b_a(attr! { class = "button" }, SingleTextNode(label.to_string()))
+    }
+
+    fn cross_reference(&mut self, id: &str, text: Option<&Text>) -> Html {
+        let text =
+            match text {
+                Some(text) => self.text(text),
+                None => SingleTextNode(id.to_string()),
+            };
+        link_a(attr! { href = format!("#{}", id) }, text)
+    }
+
+    /// The complete HTML5 document made of the `nodes`, with the document `attributes`: the `head`
+    /// element contains the title of the document header and links the `stylesheet` attribute,
+    /// and the body has the classes of the doctype and of the table of contents placement.
+    fn document(&mut self, nodes: &[Node], attributes: &BTreeMap<String, String>) -> Html {
+        let mut head = "<meta charset=\"utf-8\">".to_string();
+        if !self.options().nogenerator {
+            head.push_str(&format!("<meta name=\"generator\" content=\"asciidoctor-rs {}\">", env!("CARGO_PKG_VERSION")));
+        }
+        let title = nodes.iter()
+            .find_map(|node| match *node {
+                Header { ref title, .. } => Some(title.plain_text()),
+                _ => None,
+            });
+        if let Some(title) = title {
+            head.push_str(&format!("<title>{}</title>", escape_html(&title)));
+        }
+        if let Some(stylesheet) = attributes.get("stylesheet") {
+            head.push_str(&format!("<link {}>", attr! { rel = "stylesheet", href = stylesheet }));
+        }
+        TextNode(vec![
+            Raw(format!("<!DOCTYPE html><html><head>{}</head><body {}>", head, attr! { class = body_class(attributes) })),
+            document_content(self, nodes, attributes),
+            Raw("</body></html>".to_string()),
+        ])
+    }
+
+    fn description_list(&mut self, items: &[(Text, Text)]) -> Html {
+        let mut children = vec![];
+        for (term, definition) in items {
+            children.push(dt_a(attr! { class = "hdlist1" }, self.text(term)));
+            if definition.items.is_empty() {
+                children.push(dd(Empty));
+            }
+            else {
+                children.push(dd(p(self.text(definition))));
+            }
+        }
+        div_a(attr! { class = "dlist" }, dl(TextNode(children)))
+    }
+
+    fn footnote(&mut self, text: &Text) -> Html {
+        let text = self.text(text);
+        self.footnotes().push(text);
+        let number = self.footnotes().len();
+        let link = link_a(
+            attr! {
+                id = format!("_footnoteref_{}", number),
+                class = "footnote",
+                href = format!("#_footnotedef_{}", number),
+                title = "View footnote."
+            },
+            SingleTextNode(number.to_string()),
+        );
+        Tag(SuperScript, attr! { class = "footnote" }, Box::new(TextNode(vec![
+            SingleTextNode("[".to_string()),
+            link,
+            SingleTextNode("]".to_string()),
+        ])))
+    }
+
+    fn footnotes_div(&mut self) -> Html {
+        let footnotes: Vec<Html> = self.footnotes().drain(..).collect();
+        if footnotes.is_empty() || self.options().nofootnotes {
+            return Empty;
+        }
+        let mut children = vec![hr()];
+        for (index, text) in footnotes.into_iter().enumerate() {
+            let number = index + 1;
+            children.push(div_a(
+                attr! { class = "footnote", id = format!("_footnotedef_{}", number) },
+                TextNode(vec![
+                    link_a(attr! { href = format!("#_footnoteref_{}", number) }, SingleTextNode(number.to_string())),
+                    SingleTextNode(". ".to_string()),
+                    text,
+                ]),
+            ));
+        }
+        div_a(attr! { id = "footnotes" }, TextNode(children))
+    }
+
+    fn header(&mut self, title: &Text, authors: &[Author], revision: &Revision) -> Html {
+        let mut details = vec![];
+        for (index, author) in authors.iter().enumerate() {
+            let suffix = if index == 0 { String::new() } else { format!("_{}", index + 1) };
+            details.push(span_a(attr! { id = format!("author{}", suffix), class = "author" },
+                SingleTextNode(author.name.clone())));
+            details.push(br());
+            if let Some(ref email) = author.email {
+                let link = link_a(attr! { href = format!("mailto:{}", email) }, SingleTextNode(email.clone()));
+                details.push(span_a(attr! { id = format!("email{}", suffix), class = "email" }, link));
+                details.push(br());
+            }
+        }
+        if let Some(ref number) = revision.number {
+            let comma = if revision.date.is_some() { "," } else { "" };
+            details.push(span_a(attr! { id = "revnumber" }, SingleTextNode(format!("version {}{}", number, comma))));
+            if revision.date.is_some() {
+                details.push(SingleTextNode(" ".to_string()));
+            }
+        }
+        if let Some(ref date) = revision.date {
+            details.push(span_a(attr! { id = "revdate" }, SingleTextNode(date.clone())));
+        }
+        if let Some(ref remark) = revision.remark {
+            details.push(br());
+            details.push(span_a(attr! { id = "revremark" }, SingleTextNode(remark.clone())));
+        }
+        let title = heading_a(1, String::new(), self.text(title));
+        let details =
+            if details.is_empty() {
+                Empty
+            }
+            else {
+                div_a(attr! { class = "details" }, TextNode(details))
+            };
+        div_a(attr! { id = "header" }, TextNode(vec![title, details]))
+    }
+
+    fn example(&mut self, nodes: &[Node], title: Option<&Text>) -> Html {
+        let content = nodes.iter()
+            .map(|node| self.node(node))
+            .collect();
+        let mut children = vec![];
+        if let Some(title) = title {
+            children.push(div_a(attr! { class = "title" }, self.text(title)));
+        }
+        children.push(div_a(attr! { class = "content" }, TextNode(content)));
+        div_a(attr! { class = "exampleblock" }, TextNode(children))
+    }
+
+    fn horizontal_rule(&mut self, attributes: &[Attribute]) -> Html {
+        hr_a(attributes_to_string(attributes))
+    }
+
+    fn image(&mut self, target: &str, alt: &str, width: Option<&str>, height: Option<&str>, title: Option<&Text>)
+        -> Html
+    {
+        let mut attributes = attr! { src = target, alt = alt };
+        if let Some(width) = width {
+            attributes.push(' ');
+            attributes.push_str(&attr! { width = width });
+        }
+        if let Some(height) = height {
+            attributes.push(' ');
+            attributes.push_str(&attr! { height = height });
+        }
+        let mut children = vec![div_a(attr! { class = "content" }, img_a(attributes))];
+        // The title of an image is a caption below it.
+        if let Some(title) = title {
+            children.push(div_a(attr! { class = "title" }, self.text(title)));
+        }
+        div_a(attr! { class = "imageblock" }, TextNode(children))
+    }
+
+    fn icon(&mut self, name: &str, role: Option<&str>, size: Option<&str>) -> Html {
+        let mut icon_class = format!("fa fa-{}", name);
+        if let Some(size) = size {
+            icon_class.push_str(&format!(" fa-{}", size));
+        }
+        let class =
+            match role {
+                Some(role) => format!("icon {}", role),
+                None => "icon".to_string(),
+            };
+        span_a(attr! { class = class }, i_a(attr! { class = icon_class }, Empty))
+    }
+
+    fn inline_image(&mut self, target: &str, alt: &str) -> Html {
+        span_a(attr! { class = "image" }, img_a(attr! { src = target, alt = alt }))
     }
 
     fn item(&mut self, item: &Item) -> Html {
         match *item {
+            Item::Anchor { ref id, ref label } => self.anchor(id, label.as_deref()),
+            Item::Button(ref label) => self.button(label),
+            Item::CrossReference { ref id, ref text } => self.cross_reference(id, text.as_ref()),
+            Item::Footnote(ref text) => self.footnote(text),
+            Item::Icon { ref name, ref role, ref size } => self.icon(name, role.as_deref(), size.as_deref()),
+            Item::InlineImage { ref alt, ref target } => self.inline_image(target, alt),
+            Item::Keyboard(ref keys) => self.keyboard(keys),
+            Item::LineBreak => br(),
+            Item::Literal(ref text) => SingleTextNode(text.clone()),
+            Item::Link { ref attributes, ref target, ref text } => self.link(target, text.as_ref(), attributes),
             Item::Mark(ref text, ref attributes) => self.mark(text, attributes),
+            Item::Menu { ref menu, ref path } => self.menu(menu, path),
+            Item::Passthrough(ref content) => Raw(content.clone()),
+            Item::Quoted { double, ref text } => self.quoted(double, text),
             Item::Space => SingleTextNode(" ".to_string()),
             Item::Tag(tag, ref text, ref attributes) => self.tag(tag, text, attributes),
-            Item::Word(ref text) => SingleTextNode(text.clone()),
+            Item::Word(ref text) => self.word(text),
+        }
+    }
+
+    fn listing(&mut self, content: &str, language: Option<&str>, source: bool, title: Option<&Text>) -> Html {
+        let content_html = SingleTextNode(content.to_string());
+        let mut code =
+            match language {
+                _ if !source => pre_a(String::new(), content_html),
+                Some(language) => pre_a(attr! { class = "highlight" },
+                    code_a(attr! { class = format!("language-{}", language), data_lang = language }, content_html)),
+                None => pre_a(attr! { class = "highlight" }, code(content_html)),
+            };
+        if self.options().copy_button {
+            code = div_a(attr! { class = "copy-wrapper" }, TextNode(vec![
+                code,
+                textarea_a(attr! { class = "copy-source", hidden = "hidden" }, SingleTextNode(content.to_string())),
+            ]));
+        }
+        let mut children = vec![];
+        if let Some(title) = title {
+            children.push(div_a(attr! { class = "title" }, self.text(title)));
         }
+        children.push(div_a(attr! { class = "content" }, code));
+        div_a(attr! { class = "listingblock" }, TextNode(children))
+    }
+
+    fn keyboard(&mut self, keys: &[String]) -> Html {
+        if let [ref key] = *keys {
+            return kbd(SingleTextNode(key.clone()));
+        }
+        let mut children = vec![];
+        for (index, key) in keys.iter().enumerate() {
+            if index > 0 {
+                children.push(SingleTextNode("+".to_string()));
+            }
+            children.push(kbd(SingleTextNode(key.clone())));
+        }
+        span_a(attr! { class = "keyseq" }, TextNode(children))
+    }
+
+    fn link(&mut self, target: &str, text: Option<&Text>, attributes: &[Attribute]) -> Html {
+        let (mut link_attributes, text) =
+            match text {
+                Some(text) => (attr! { href = target }, self.text(text)),
+                None => (attr! { href = target, class = "bare" }, SingleTextNode(target.to_string())),
+            };
+        if let Some(window) = find_named_attribute(attributes, "window") {
+            link_attributes.push(' ');
+            link_attributes.push_str(&attr! { target = window });
+            // The opened page must not have access to this page.
+            if window == "_blank" {
+                link_attributes.push_str(" rel=\"noopener\"");
+            }
+        }
+        link_a(link_attributes, text)
+    }
+
+    fn list_item(&mut self, item: &ListItem) -> Html {
+        let text = p(self.text(&item.text));
+        match item.nested {
+            Some(ref nested) => li(TextNode(vec![text, self.node(nested)])),
+            None => li(text),
+        }
+    }
+
+    fn literal(&mut self, content: &str) -> Html {
+        let content = div_a(attr! { class = "content" }, pre_a(String::new(), SingleTextNode(content.to_string())));
+        div_a(attr! { class = "literalblock" }, content)
     }
 
     fn mark(&mut self, text: &Text, attributes: &[Attribute]) -> Html {
@@ -79,119 +556,740 @@ pub trait HtmlGen {
         }
     }
 
+    fn menu(&mut self, menu: &str, path: &[String]) -> Html {
+        let menu = SingleTextNode(menu.to_string());
+        if path.is_empty() {
+            return b_a(attr! { class = "menuref" }, menu);
+        }
+        let mut children = vec![b_a(attr! { class = "menu" }, menu)];
+        for (index, item) in path.iter().enumerate() {
+            let class = if index + 1 == path.len() { "menuitem" } else { "submenu" };
+            children.push(Raw("&#160;".to_string()));
+            children.push(b_a(attr! { class = "caret" }, Raw("&#8250;".to_string())));
+            children.push(SingleTextNode(" ".to_string()));
+            children.push(b_a(attr! { class = class }, SingleTextNode(item.clone())));
+        }
+        span_a(attr! { class = "menuseq" }, TextNode(children))
+    }
+
     fn node(&mut self, node: &Node) -> Html {
-        match *node {
-            HorizontalRule => self.horizontal_rule(),
-            PageBreak => self.page_break(),
-            Paragraph(ref text) => self.paragraph(text),
+        let mut html =
+            if let Some(hook) = self.hook(node.kind()) {
+                hook(node)
+            }
+            else {
+                match *node {
+                    Admonition { ref caption, kind, ref text, .. } => self.admonition(kind, caption, text),
+                    AdmonitionBlock { ref caption, kind, ref nodes, .. } => self.admonition_block(kind, caption, nodes),
+                    DescriptionList(ref items, _) => self.description_list(items),
+                    Example { ref nodes, ref title, .. } => self.example(nodes, title.as_ref()),
+                    Header { ref authors, ref revision, ref title, .. } => self.header(title, authors, revision),
+                    HorizontalRule(ref attributes, _) => self.horizontal_rule(attributes),
+                    Image { ref alt, ref height, ref target, ref title, ref width, .. } =>
+                        self.image(target, alt, width.as_deref(), height.as_deref(), title.as_ref()),
+                    Listing { ref content, ref language, source, ref title, .. } =>
+                        self.listing(content, language.as_deref(), source, title.as_ref()),
+                    Literal(ref content, _) => self.literal(content),
+                    Open(ref nodes, _) => self.open(nodes),
+                    OrderedList { ref attributes, ref items, level, start, .. } =>
+                        self.ordered_list(items, level, start, attributes),
+                    PageBreak(_) => self.page_break(),
+                    Paragraph(ref text, ref attributes, _) => self.paragraph(text, attributes),
+                    Passthrough(ref content, _) => self.passthrough(content),
+                    Quote { ref author, ref content, ref source, .. } => self.quote(content, author.as_deref(), source.as_deref()),
+                    Section { ref attributes, level, ref title, .. } => self.section(level, title, attributes),
+                    Sidebar { ref nodes, ref title, .. } => self.sidebar(nodes, title.as_ref()),
+                    SubstitutedBlock { listing, ref text, .. } => self.substituted_block(listing, text),
+                    Node::Table { ref attributes, ref rows, .. } => self.table(rows, attributes),
+                    // The table of contents is generated for the whole document by gen_document().
+                    Toc(_) => Empty,
+                    UnorderedList(ref items, ref attributes, _) => self.unordered_list(items, attributes),
+                }
+            };
+        if self.options().source_lines {
+            html.add_attributes(&attr! { data_source_line = node.pos().line });
+        }
+        html
+    }
+
+    fn open(&mut self, nodes: &[Node]) -> Html {
+        let content = nodes.iter()
+            .map(|node| self.node(node))
+            .collect();
+        div_a(attr! { class = "openblock" }, div_a(attr! { class = "content" }, TextNode(content)))
+    }
+
+    fn ordered_list(&mut self, items: &[ListItem], level: usize, start: Option<usize>, attributes: &[Attribute])
+        -> Html
+    {
+        let items = items.iter()
+            .map(|item| self.list_item(item))
+            .collect();
+        let default_style = DEFAULT_NUMBERING_STYLES[level.saturating_sub(1) % DEFAULT_NUMBERING_STYLES.len()];
+        let (style, list_type) = attributes.iter()
+            .find_map(|attribute| match *attribute {
+                Role(ref role) => NUMBERING_STYLES.iter().find(|&&(style, _)| style == role),
+                _ => None,
+            })
+            .or_else(|| NUMBERING_STYLES.iter().find(|&&(style, _)| style == default_style))
+            .cloned()
+            .unwrap_or(("arabic", None));
+        let mut list_attributes = attr! { class = style };
+        if let Some(list_type) = list_type {
+            list_attributes.push_str(&format!(" type=\"{}\"", list_type));
+        }
+        if let Some(start) = start {
+            list_attributes.push(' ');
+            list_attributes.push_str(&attr! { start = start });
         }
+        div_a(attr! { class = format!("olist {}", style) }, ol_a(list_attributes, TextNode(items)))
     }
 
     fn page_break(&mut self) -> Html {
-        div_a(
-            attr! { style = "page-break-after: always;" },
-            Empty
-        )
+        let attributes =
+            if self.options().csp_safe {
+                attr! { class = "page-break" }
+            }
+            else {
+                attr! { style = "page-break-after: always;" }
+            };
+        div_a(attributes, Empty)
     }
 
-    fn paragraph(&mut self, text: &Text) -> Html {
+    fn paragraph(&mut self, text: &Text, attributes: &[Attribute]) -> Html {
         let text = self.text(text);
-        div_a(
-            attr! { class = "paragraph" },
-            p(text),
-        )
+        let attributes =
+            match find_id_attribute(attributes) {
+                Some(id) => attr! { id = id, class = "paragraph" },
+                None => attr! { class = "paragraph" },
+            };
+        div_a(attributes, p(text))
+    }
+
+    fn passthrough(&mut self, content: &str) -> Html {
+        Raw(content.to_string())
+    }
+
+    fn quote(&mut self, content: &[Node], author: Option<&str>, source: Option<&str>) -> Html {
+        let content = content.iter()
+            .map(|node| self.node(node))
+            .collect();
+        let mut children = vec![blockquote(TextNode(content))];
+        if author.is_some() || source.is_some() {
+            let mut attribution = vec![];
+            if let Some(author) = author {
+                attribution.push(Raw("&#8212; ".to_string()));
+                attribution.push(SingleTextNode(author.to_string()));
+            }
+            if let Some(source) = source {
+                if author.is_some() {
+                    attribution.push(br());
+                }
+                attribution.push(cite(SingleTextNode(source.to_string())));
+            }
+            children.push(div_a(attr! { class = "attribution" }, TextNode(attribution)));
+        }
+        div_a(attr! { class = "quoteblock" }, TextNode(children))
+    }
+
+    fn quoted(&mut self, double: bool, text: &Text) -> Html {
+        let ((open_name, open_number), (close_name, close_number)) =
+            if double {
+                (("ldquo", 8220), ("rdquo", 8221))
+            }
+            else {
+                (("lsquo", 8216), ("rsquo", 8217))
+            };
+        let named_entities = self.options().named_entities;
+        TextNode(vec![
+            Raw(entity(open_name, open_number, named_entities)),
+            self.text(text),
+            Raw(entity(close_name, close_number, named_entities)),
+        ])
+    }
+
+    fn section(&mut self, level: usize, title: &Text, attributes: &[Attribute]) -> Html {
+        let id = unique_section_id(self.ids(), title, attributes);
+        let mut title = self.text(title);
+        if self.options().sectlinks {
+            title = link_a(attr! { class = "link", href = format!("#{}", id) }, title);
+        }
+        heading_a(level + 1, attr! { id = id }, title)
+    }
+
+    fn table(&mut self, rows: &[Vec<Text>], attributes: &[Attribute]) -> Html {
+        let mut rows = rows;
+        let mut head = Empty;
+        if has_option(attributes, "header") {
+            if let Some((first, rest)) = rows.split_first() {
+                head = thead(self.table_row(first, true));
+                rows = rest;
+            }
+        }
+        let mut foot = Empty;
+        if has_option(attributes, "footer") {
+            if let Some((last, rest)) = rows.split_last() {
+                foot = tfoot(self.table_row(last, false));
+                rows = rest;
+            }
+        }
+        let body = rows.iter()
+            .map(|row| self.table_row(row, false))
+            .collect();
+        let class = "tableblock frame-all grid-all stretch";
+        let attributes =
+            match find_id_attribute(attributes) {
+                Some(id) => attr! { id = id, class = class },
+                None => attr! { class = class },
+            };
+        table_a(attributes, TextNode(vec![head, tbody(TextNode(body)), foot]))
+    }
+
+    fn toc(&mut self, nodes: &[Node], levels: usize) -> Html {
+        let sections: Vec<_> = nodes.iter()
+            .filter_map(|node| match *node {
+                Section { ref attributes, level, ref title, .. } if level >= 1 => Some((level, attributes, title)),
+                _ => None,
+            })
+            .collect();
+        // The ids are generated like when generating the sections, in order, including the ones
+        // deeper than the levels of the table of contents.
+        let mut ids = BTreeSet::new();
+        let mut entries = vec![];
+        for (level, attributes, title) in sections {
+            let id = unique_section_id(&mut ids, title, attributes);
+            if level <= levels {
+                entries.push((level, id, self.text(title)));
+            }
+        }
+        let list = toc_list(&mut entries.into_iter().peekable(), 1);
+        let attributes =
+            if self.options().aria {
+                attr! { id = "toc", class = "toc", role = "doc-toc" }
+            }
+            else {
+                attr! { id = "toc", class = "toc" }
+            };
+        div_a(attributes, list)
+    }
+
+    fn table_row(&mut self, cells: &[Text], header: bool) -> Html {
+        let cells = cells.iter()
+            .map(|cell| {
+                let text = self.text(cell);
+                let class = attr! { class = "tableblock halign-left valign-top" };
+                if header {
+                    th_a(class, text)
+                }
+                else {
+                    td_a(class, p_a(attr! { class = "tableblock" }, text))
+                }
+            })
+            .collect();
+        tr(TextNode(cells))
     }
 
     fn tag(&mut self, tag: Tag, text: &Text, attributes: &[Attribute]) -> Html {
         let text = self.text(text);
-        let tag = Tag(tag, attributes_to_string(attributes), Box::new(text));
-        if let Some(id) = find_id_attribute(attributes) {
-            Seq(Box::new(A(id)), Box::new(tag))
-        } else {
-            tag
+        Tag(tag, attributes_to_string(attributes), Box::new(text))
+    }
+
+    fn sidebar(&mut self, nodes: &[Node], title: Option<&Text>) -> Html {
+        let mut content = vec![];
+        if let Some(title) = title {
+            content.push(div_a(attr! { class = "title" }, self.text(title)));
         }
+        content.extend(nodes.iter().map(|node| self.node(node)));
+        div_a(attr! { class = "sidebarblock" }, div_a(attr! { class = "content" }, TextNode(content)))
+    }
+
+    fn substituted_block(&mut self, listing: bool, text: &Text) -> Html {
+        // The spaces are kept as is, even when minifying, since the content is preformatted.
+        let content = TextNode(text.items.iter().map(|item| self.item(item)).collect());
+        let (class, pre) =
+            if listing {
+                ("listingblock", pre_a(attr! { class = "highlight" }, code(content)))
+            }
+            else {
+                ("literalblock", pre_a(String::new(), content))
+            };
+        div_a(attr! { class = class }, div_a(attr! { class = "content" }, pre))
     }
 
     fn text(&mut self, text: &Text) -> Html {
         let mut texts = vec![];
+        let mut after_space = false;
         for item in &text.items {
-            texts.push(self.item(item));
+            let is_space = *item == Item::Space;
+            if !(is_space && after_space && self.options().minify) {
+                texts.push(self.item(item));
+            }
+            after_space = is_space;
         }
         TextNode(texts)
     }
+
+    fn unordered_list(&mut self, items: &[ListItem], attributes: &[Attribute]) -> Html {
+        let items = items.iter()
+            .map(|item| self.list_item(item))
+            .collect();
+        let style = attributes.iter()
+            .find_map(|attribute| match *attribute {
+                Role(ref role) if MARKER_STYLES.contains(&role.as_str()) => Some(role),
+                _ => None,
+            });
+        match style {
+            Some(style) =>
+                div_a(attr! { class = format!("ulist {}", style) }, ul_a(attr! { class = style }, TextNode(items))),
+            None => div_a(attr! { class = "ulist" }, ul_a(String::new(), TextNode(items))),
+        }
+    }
+
+    fn word(&mut self, word: &str) -> Html {
+        let options = self.options();
+        if options.noreplacements {
+            Raw(escape_text(word))
+        }
+        else {
+            Raw(replace_characters(word, options.named_entities))
+        }
+    }
 }
 
-impl HtmlGen for Generator {}
+impl HtmlGen for Generator {
+    fn footnotes(&mut self) -> &mut Vec<Html> {
+        &mut self.footnotes
+    }
+
+    fn hook(&self, kind: NodeKind) -> Option<&Hook> {
+        self.hooks.get(&kind)
+    }
+
+    fn ids(&mut self) -> &mut BTreeSet<String> {
+        &mut self.ids
+    }
+
+    fn options(&self) -> &Options {
+        &self.options
+    }
+}
 
 /// Represent an HTML node with its children.
 pub enum Html {
     A(Id),
+    B(String, Box<Html>),
+    Blockquote(Box<Html>),
+    Br,
+    Cite(Box<Html>),
+    Code(String, Box<Html>),
+    Dd(Box<Html>),
     Div(String, Box<Html>),
+    Dl(Box<Html>),
+    Dt(String, Box<Html>),
     Empty,
-    Hr,
+    H(usize, String, Box<Html>),
+    Hr(String),
+    I(String, Box<Html>),
+    Img(String),
+    Kbd(Box<Html>),
+    Li(Box<Html>),
+    Link(String, Box<Html>),
     Mark(Box<Html>),
+    Ol(String, Box<Html>),
     P(Box<Html>),
+    PA(String, Box<Html>),
+    Pre(String, Box<Html>),
+    Raw(String),
     Seq(Box<Html>, Box<Html>),
     SingleTextNode(String),
     Span(String, Box<Html>),
+    Table(String, Box<Html>),
     Tag(Tag, String, Box<Html>),
+    Tbody(Box<Html>),
+    Td(String, Box<Html>),
+    Textarea(String, Box<Html>),
     TextNode(Vec<Html>),
+    Tfoot(Box<Html>),
+    Th(String, Box<Html>),
+    Thead(Box<Html>),
+    Tr(Box<Html>),
+    Ul(String, Box<Html>),
 }
 
 impl Html {
-    fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+    /// Add the `attributes` to the root element of this HTML code: the first element of a
+    /// sequence or the first tag of raw HTML code.
+    fn add_attributes(&mut self, attributes: &str) {
+        match *self {
+            B(ref mut current, _) | Code(ref mut current, _) | Div(ref mut current, _) | Dt(ref mut current, _) |
+                H(_, ref mut current, _) | Hr(ref mut current) | I(ref mut current, _) | Img(ref mut current) |
+                Link(ref mut current, _) | Ol(ref mut current, _) | PA(ref mut current, _) | Pre(ref mut current, _) |
+                Span(ref mut current, _) | Table(ref mut current, _) | Tag(_, ref mut current, _) |
+                Td(ref mut current, _) | Textarea(ref mut current, _) | Th(ref mut current, _) | Ul(ref mut current, _) =>
+            {
+                if !current.is_empty() {
+                    current.push(' ');
+                }
+                current.push_str(attributes);
+            },
+            P(_) => {
+                if let P(children) = mem::replace(self, Empty) {
+                    *self = PA(attributes.to_string(), children);
+                }
+            },
+            Raw(ref mut html) => {
+                // The name of the tag starts after the `<`.
+                let start = html.len() - html.trim_start().len() + 1;
+                if html[start - 1..].starts_with('<') &&
+                    html[start..].starts_with(|character: char| character.is_ascii_alphabetic())
+                {
+                    let name_end = html[start..].find(|character: char| !character.is_ascii_alphanumeric())
+                        .map_or(html.len(), |index| start + index);
+                    html.insert_str(name_end, &format!(" {}", attributes));
+                }
+            },
+            Seq(ref mut first, ref mut second) => {
+                if first.is_element() {
+                    first.add_attributes(attributes);
+                }
+                else {
+                    second.add_attributes(attributes);
+                }
+            },
+            TextNode(ref mut children) => {
+                if let Some(child) = children.iter_mut().find(|child| child.is_element()) {
+                    child.add_attributes(attributes);
+                }
+            },
+            _ => (),
+        }
+    }
+
+    /// Check if this is an element, rather than text or nothing.
+    fn is_element(&self) -> bool {
+        !matches!(*self, Empty | SingleTextNode(_))
+    }
+
+    /// Get the name, the attributes and the children of this element, if it is a block element,
+    /// which is written on its own lines when pretty printing.
+    fn block(&self) -> Option<(String, &str, Option<&Html>)> {
+        let (name, attributes, children) =
+            match *self {
+                Blockquote(ref children) => ("blockquote".to_string(), "", Some(children)),
+                Dd(ref children) => ("dd".to_string(), "", Some(children)),
+                Div(ref attributes, ref children) => ("div".to_string(), attributes.as_str(), Some(children)),
+                Dl(ref children) => ("dl".to_string(), "", Some(children)),
+                Dt(ref attributes, ref children) => ("dt".to_string(), attributes.as_str(), Some(children)),
+                H(level, ref attributes, ref children) => (format!("h{}", level), attributes.as_str(), Some(children)),
+                Hr(ref attributes) => ("hr".to_string(), attributes.as_str(), None),
+                Li(ref children) => ("li".to_string(), "", Some(children)),
+                Ol(ref attributes, ref children) => ("ol".to_string(), attributes.as_str(), Some(children)),
+                P(ref children) => ("p".to_string(), "", Some(children)),
+                PA(ref attributes, ref children) => ("p".to_string(), attributes.as_str(), Some(children)),
+                Pre(ref attributes, ref children) => ("pre".to_string(), attributes.as_str(), Some(children)),
+                Table(ref attributes, ref children) => ("table".to_string(), attributes.as_str(), Some(children)),
+                Tbody(ref children) => ("tbody".to_string(), "", Some(children)),
+                Td(ref attributes, ref children) => ("td".to_string(), attributes.as_str(), Some(children)),
+                Tfoot(ref children) => ("tfoot".to_string(), "", Some(children)),
+                Th(ref attributes, ref children) => ("th".to_string(), attributes.as_str(), Some(children)),
+                Thead(ref children) => ("thead".to_string(), "", Some(children)),
+                Tr(ref children) => ("tr".to_string(), "", Some(children)),
+                Ul(ref attributes, ref children) => ("ul".to_string(), attributes.as_str(), Some(children)),
+                _ => return None,
+            };
+        Some((name, attributes, children.map(|children| &**children)))
+    }
+
+    /// Get the elements of this sequence of elements, or this element itself.
+    fn flatten<'a>(&'a self, elements: &mut Vec<&'a Html>) {
+        match *self {
+            Empty => (),
+            Seq(ref child1, ref child2) => {
+                child1.flatten(elements);
+                child2.flatten(elements);
+            },
+            TextNode(ref nodes) => {
+                for node in nodes {
+                    node.flatten(elements);
+                }
+            },
+            _ => elements.push(self),
+        }
+    }
+
+    /// Check if this element is or contains a block element.
+    fn has_block(&self) -> bool {
+        let mut elements = vec![];
+        self.flatten(&mut elements);
+        elements.iter().any(|element| element.block().is_some())
+    }
+
+    fn write<W: Sink>(&self, writer: &mut W) -> Result<()> {
         match *self {
             A(ref id) => tag_a_without_child("a", &attr! { id = id }, writer),
+            B(ref attributes, ref children) => tag_a("b", attributes, children, writer),
+            Blockquote(ref children) => tag("blockquote", children, writer),
+            Br => write_text("<br/>", writer),
+            Cite(ref children) => tag("cite", children, writer),
+            Code(ref attributes, ref children) => tag_a("code", attributes, children, writer),
+            Dd(ref children) => tag("dd", children, writer),
             Div(ref attributes, ref children) => tag_a("div", attributes, children, writer),
+            Dl(ref children) => tag("dl", children, writer),
+            Dt(ref attributes, ref children) => tag_a("dt", attributes, children, writer),
             Empty => Ok(()),
-            Hr => write_text("<hr/>", writer),
+            H(level, ref attributes, ref children) => tag_a(&format!("h{}", level), attributes, children, writer),
+            Hr(ref attributes) if attributes.is_empty() => write_text("<hr/>", writer),
+            Hr(ref attributes) => write!(writer, "<hr {}/>", attributes),
+            I(ref attributes, ref children) => tag_a("i", attributes, children, writer),
+            Img(ref attributes) => write!(writer, "<img {}/>", attributes),
+            Kbd(ref children) => tag("kbd", children, writer),
+            Li(ref children) => tag("li", children, writer),
+            Link(ref attributes, ref children) => tag_a("a", attributes, children, writer),
             Mark(ref children) => tag("mark", children, writer),
+            Ol(ref attributes, ref children) => tag_a("ol", attributes, children, writer),
             P(ref children) => tag("p", children, writer),
+            PA(ref attributes, ref children) => tag_a("p", attributes, children, writer),
+            Pre(ref attributes, ref children) => tag_a("pre", attributes, children, writer),
+            Raw(ref content) => write_text(content, writer),
             Seq(ref child1, ref child2) => {
                 child1.write(writer)?;
                 child2.write(writer)
             },
-            SingleTextNode(ref text) => write_text(text, writer),
+            SingleTextNode(ref text) => write_text(&escape_html(text), writer),
             Span(ref attributes, ref children) => tag_a("span", attributes, children, writer),
+            Table(ref attributes, ref children) => tag_a("table", attributes, children, writer),
             Tag(ref tag, ref attributes, ref children) => tag_a(tag.to_string(), attributes, children, writer),
+            Tbody(ref children) => tag("tbody", children, writer),
+            Td(ref attributes, ref children) => tag_a("td", attributes, children, writer),
+            Textarea(ref attributes, ref children) => tag_a("textarea", attributes, children, writer),
             TextNode(ref nodes) => {
                 for node in nodes {
                     node.write(writer)?;
                 }
                 Ok(())
             },
+            Tfoot(ref children) => tag("tfoot", children, writer),
+            Th(ref attributes, ref children) => tag_a("th", attributes, children, writer),
+            Thead(ref children) => tag("thead", children, writer),
+            Tr(ref children) => tag("tr", children, writer),
+            Ul(ref attributes, ref children) => tag_a("ul", attributes, children, writer),
+        }
+    }
+
+    /// Write the elements with the block elements on their own lines, indented by two spaces for
+    /// each of the `depth` levels of nesting. The runs of inline elements are on their own lines
+    /// too.
+    fn write_pretty<W: Sink>(&self, writer: &mut W, depth: usize) -> Result<()> {
+        let indentation = "  ".repeat(depth);
+        let mut elements = vec![];
+        self.flatten(&mut elements);
+        let mut in_line = false;
+        for element in elements {
+            match element.block() {
+                Some((name, attributes, Some(children))) if children.has_block() => {
+                    if in_line {
+                        write_text("\n", writer)?;
+                        in_line = false;
+                    }
+                    if attributes.is_empty() {
+                        writeln!(writer, "{}<{}>", indentation, name)?;
+                    }
+                    else {
+                        writeln!(writer, "{}<{} {}>", indentation, name, attributes)?;
+                    }
+                    children.write_pretty(writer, depth + 1)?;
+                    writeln!(writer, "{}</{}>", indentation, name)?;
+                },
+                Some(_) => {
+                    if in_line {
+                        write_text("\n", writer)?;
+                        in_line = false;
+                    }
+                    write_text(&indentation, writer)?;
+                    element.write(writer)?;
+                    write_text("\n", writer)?;
+                },
+                None => {
+                    if !in_line {
+                        write_text(&indentation, writer)?;
+                        in_line = true;
+                    }
+                    element.write(writer)?;
+                },
+            }
         }
+        if in_line {
+            write_text("\n", writer)?;
+        }
+        Ok(())
     }
 }
 
+/// Create the table of an admonition, with its caption and its `content`.
+fn admonition_table(kind: AdmonitionKind, caption: &str, content: Html, aria: bool) -> Html {
+    let class = format!("admonitionblock {}", kind.name());
+    let attributes =
+        if aria {
+            attr! { class = class, role = "note" }
+        }
+        else {
+            attr! { class = class }
+        };
+    div_a(attributes, table(tr(TextNode(vec![
+        td_a(attr! { class = "icon" }, div_a(attr! { class = "title" }, SingleTextNode(caption.to_string()))),
+        td_a(attr! { class = "content" }, content),
+    ]))))
+}
+
 fn attributes_to_string(attributes: &[Attribute]) -> String {
     let mut string = String::new();
+    let mut roles = vec![];
     for attribute in attributes {
         match *attribute {
-            Attribute::Id(ref id) => string.push_str(&format!("id=\"{}\"", id)), // TODO: needs space around?
-            Role(ref role) => string.push_str(&format!("class=\"{}\"", role)), // TODO: needs space around?
+            Attribute::Id(ref id) => string = attr! { id = id },
+            Attribute::Named { .. } | Attribute::Option(_) | Attribute::Positional(_) => (),
+            Role(ref role) => roles.push(role.as_str()),
         }
     }
+    if !roles.is_empty() {
+        if !string.is_empty() {
+            string.push(' ');
+        }
+        string.push_str(&format!("class=\"{}\"", escape_html(&roles.join(" "))));
+    }
     string
 }
 
+/// Escape the characters of `input` that have a special meaning in HTML.
+/// This only allocates a new string when `input` contains such a character.
+pub fn escape_html(input: &str) -> Cow<'_, str> {
+    if !input.contains(['&', '<', '>', '"']) {
+        return Cow::Borrowed(input);
+    }
+    let mut escaped = String::with_capacity(input.len());
+    for character in input.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(character),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+/// Get the classes of the body of a standalone document from the document `attributes`, like
+/// `article toc2 toc-left`.
+fn body_class(attributes: &BTreeMap<String, String>) -> String {
+    let mut class = attributes.get("doctype")
+        .filter(|doctype| !doctype.is_empty())
+        .cloned()
+        .unwrap_or_else(|| "article".to_string());
+    if let Some(placement @ "left") | Some(placement @ "right") = attributes.get("toc").map(String::as_str) {
+        class.push_str(&format!(" toc2 toc-{}", placement));
+    }
+    class
+}
+
+/// Create a b element with attributes.
+pub fn b_a(attributes: String, children: Html) -> Html {
+    B(attributes, Box::new(children))
+}
+
+/// Create a blockquote element.
+pub fn blockquote(children: Html) -> Html {
+    Blockquote(Box::new(children))
+}
+
+/// Create a br element.
+pub fn br() -> Html {
+    Br
+}
+
+/// Create a cite element.
+pub fn cite(children: Html) -> Html {
+    Cite(Box::new(children))
+}
+
+/// Create a code element.
+pub fn code(children: Html) -> Html {
+    Code(String::new(), Box::new(children))
+}
+
+/// Create a code element with attributes.
+pub fn code_a(attributes: String, children: Html) -> Html {
+    Code(attributes, Box::new(children))
+}
+
+/// Create a dd element.
+pub fn dd(children: Html) -> Html {
+    Dd(Box::new(children))
+}
+
 /// Create a div element with attributes.
 pub fn div_a(attributes: String, children: Html) -> Html {
     Div(attributes, Box::new(children))
 }
 
-fn find_id_attribute(attributes: &[Attribute]) -> Option<String> {
-    for attribute in attributes {
-        if let Attribute::Id(ref id) = *attribute {
-            return Some(id.clone());
-        }
-    }
-    None
+/// Create a dl element.
+pub fn dl(children: Html) -> Html {
+    Dl(Box::new(children))
+}
+
+/// Create a dt element with attributes.
+pub fn dt_a(attributes: String, children: Html) -> Html {
+    Dt(attributes, Box::new(children))
+}
+
+/// Get the value of the named attribute `name`, like `window=_blank`, if it is in the
+/// `attributes`.
+fn find_named_attribute<'a>(attributes: &'a [Attribute], name: &str) -> Option<&'a str> {
+    attributes.iter()
+        .find_map(|attribute| match *attribute {
+            Attribute::Named { name: ref attribute_name, ref value } if attribute_name == name => Some(value.as_str()),
+            _ => None,
+        })
+}
+
+/// Create a heading element (h1, h2, …) with attributes.
+pub fn heading_a(level: usize, attributes: String, children: Html) -> Html {
+    H(level, attributes, Box::new(children))
 }
 
 /// Create a hr element.
 pub fn hr() -> Html {
-    Hr
+    Hr(String::new())
+}
+
+/// Create a hr element with attributes.
+pub fn hr_a(attributes: String) -> Html {
+    Hr(attributes)
+}
+
+/// Create an i element with attributes.
+pub fn i_a(attributes: String, children: Html) -> Html {
+    I(attributes, Box::new(children))
+}
+
+/// Create an img element with attributes.
+pub fn img_a(attributes: String) -> Html {
+    Img(attributes)
+}
+
+/// Create a kbd element.
+pub fn kbd(children: Html) -> Html {
+    Kbd(Box::new(children))
+}
+
+/// Create a li element.
+pub fn li(children: Html) -> Html {
+    Li(Box::new(children))
+}
+
+/// Create a link element with attributes.
+pub fn link_a(attributes: String, children: Html) -> Html {
+    Link(attributes, Box::new(children))
 }
 
 /// Create a mark element.
@@ -199,37 +1297,129 @@ pub fn mark(children: Html) -> Html {
     Mark(Box::new(children))
 }
 
+/// Create an ol element with attributes.
+pub fn ol_a(attributes: String, children: Html) -> Html {
+    Ol(attributes, Box::new(children))
+}
+
 /// Create a p element.
 pub fn p(children: Html) -> Html {
     P(Box::new(children))
 }
 
+/// Create a p element with attributes.
+pub fn p_a(attributes: String, children: Html) -> Html {
+    PA(attributes, Box::new(children))
+}
+
+/// Create a pre element with attributes.
+pub fn pre_a(attributes: String, children: Html) -> Html {
+    Pre(attributes, Box::new(children))
+}
+
+/// Write the `html` in the `writer`, pretty printed with the `pretty` option of the generator.
+fn write_html<G: HtmlGen + ?Sized, W: Sink>(gen: &G, html: &Html, writer: &mut W) -> Result<()> {
+    let options = gen.options();
+    if options.pretty && !options.minify {
+        html.write_pretty(writer, 0)
+    }
+    else {
+        html.write(writer)
+    }
+}
+
+/// Create the nested lists of the table of contents, with the entries at `level` or deeper.
+fn toc_list<I: Iterator<Item=(usize, Id, Html)>>(entries: &mut Peekable<I>, level: usize) -> Html {
+    let mut items = vec![];
+    while let Some((_, id, title)) = entries.next_if(|&(entry_level, ..)| entry_level >= level) {
+        let mut children = vec![link_a(attr! { href = format!("#{}", id) }, title)];
+        if matches!(entries.peek(), Some(&(next_level, ..)) if next_level > level) {
+            children.push(toc_list(entries, level + 1));
+        }
+        items.push(li(TextNode(children)));
+    }
+    ul_a(attr! { class = format!("sectlevel{}", level) }, TextNode(items))
+}
+
 /// Create a span element.
 pub fn span_a(attributes: String, children: Html) -> Html {
     Span(attributes, Box::new(children))
 }
 
-fn tag<W: Write>(name: &str, children: &Html, writer: &mut W) -> Result<()> {
+/// Create a table element.
+pub fn table(children: Html) -> Html {
+    Table(String::new(), Box::new(children))
+}
+
+/// Create a table element with attributes.
+pub fn table_a(attributes: String, children: Html) -> Html {
+    Table(attributes, Box::new(children))
+}
+
+/// Create a tbody element.
+pub fn tbody(children: Html) -> Html {
+    Tbody(Box::new(children))
+}
+
+fn tag<W: Sink>(name: &str, children: &Html, writer: &mut W) -> Result<()> {
     write!(writer, "<{}>", name)?;
     children.write(writer)?;
     write!(writer, "</{}>", name)?;
     Ok(())
 }
 
-fn tag_a<W: Write>(name: &str, attributes: &str, children: &Html, writer: &mut W) -> Result<()> {
+fn tag_a<W: Sink>(name: &str, attributes: &str, children: &Html, writer: &mut W) -> Result<()> {
+    if attributes.is_empty() {
+        return tag(name, children, writer);
+    }
     write!(writer, "<{} {}>", name, attributes)?;
     children.write(writer)?;
     write!(writer, "</{}>", name)?;
     Ok(())
 }
 
-fn tag_a_without_child<W: Write>(name: &str, attributes: &str, writer: &mut W) -> Result<()> {
+fn tag_a_without_child<W: Sink>(name: &str, attributes: &str, writer: &mut W) -> Result<()> {
     write!(writer, "<{} {}>", name, attributes)?;
     write!(writer, "</{}>", name)?;
     Ok(())
 }
 
-fn write_text<W: Write>(text: &str, writer: &mut W) -> Result<()> {
+/// Create a td element with attributes.
+pub fn td_a(attributes: String, children: Html) -> Html {
+    Td(attributes, Box::new(children))
+}
+
+/// Create a tfoot element.
+pub fn tfoot(children: Html) -> Html {
+    Tfoot(Box::new(children))
+}
+
+/// Create a th element with attributes.
+pub fn th_a(attributes: String, children: Html) -> Html {
+    Th(attributes, Box::new(children))
+}
+
+/// Create a thead element.
+pub fn thead(children: Html) -> Html {
+    Thead(Box::new(children))
+}
+
+/// Create a textarea element with attributes.
+pub fn textarea_a(attributes: String, children: Html) -> Html {
+    Textarea(attributes, Box::new(children))
+}
+
+/// Create a tr element.
+pub fn tr(children: Html) -> Html {
+    Tr(Box::new(children))
+}
+
+/// Create a ul element with attributes.
+pub fn ul_a(attributes: String, children: Html) -> Html {
+    Ul(attributes, Box::new(children))
+}
+
+fn write_text<W: Sink>(text: &str, writer: &mut W) -> Result<()> {
     write!(writer, "{}", text)?;
     Ok(())
 }
@@ -0,0 +1,403 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Generate CommonMark from the asciidoctor nodes.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::char;
+
+use error::Result;
+use gen::{Sink, REPLACEMENTS};
+use gen::html::escape_html;
+use node::{AdmonitionKind, Author, ListItem, Node};
+use node::Node::*;
+use node::{Item, Tag, Text};
+
+/// The characters escaped with a backslash in the text, since they are formatting marks.
+const ESCAPED_CHARS: &str = "\\`*_[]<>|";
+
+/// Write the resulting Markdown code for the specified `node`, followed by a newline, in the
+/// `writer`. The blocks must be separated by an empty line, which gen_document() does.
+pub fn gen<G: MarkdownGen, W: Sink>(gen: &mut G, node: &Node, writer: &mut W) -> Result<()> {
+    let block = gen.node(node);
+    if !block.is_empty() {
+        writeln!(writer, "{}", block)?;
+    }
+    Ok(())
+}
+
+/// Write the Markdown document made of the `nodes` in the `writer`, with the blocks separated by
+/// an empty line and followed by the definitions of the footnotes.
+pub fn gen_document<G: MarkdownGen, W: Sink>(gen: &mut G, nodes: &[Node], writer: &mut W) -> Result<()> {
+    let mut blocks: Vec<_> = nodes.iter()
+        .map(|node| gen.node(node))
+        .filter(|block| !block.is_empty())
+        .collect();
+    let footnotes: Vec<_> = gen.footnotes().drain(..)
+        .enumerate()
+        .map(|(index, footnote)| format!("[^{}]: {}", index + 1, footnote))
+        .collect();
+    if !footnotes.is_empty() {
+        blocks.push(footnotes.join("\n"));
+    }
+    if !blocks.is_empty() {
+        writeln!(writer, "{}", blocks.join("\n\n"))?;
+    }
+    Ok(())
+}
+
+/// Genarate Markdown code from a asciidoctor node.
+pub trait MarkdownGen {
+    /// The footnotes collected while generating the document.
+    fn footnotes(&mut self) -> &mut Vec<String>;
+
+    fn admonition(&mut self, _kind: AdmonitionKind, caption: &str, text: &Text) -> String {
+        format!("**{}:** {}", caption, self.text(text))
+    }
+
+    fn admonition_block(&mut self, _kind: AdmonitionKind, caption: &str, nodes: &[Node]) -> String {
+        let mut blocks = vec![format!("**{}:**", caption)];
+        blocks.append(&mut self.nodes(nodes));
+        blocks.join("\n\n")
+    }
+
+    fn description_list(&mut self, items: &[(Text, Text)]) -> String {
+        let items: Vec<_> = items.iter()
+            .map(|(term, definition)| {
+                let term = self.text(term);
+                if definition.items.is_empty() {
+                    format!("- **{}**", term)
+                }
+                else {
+                    format!("- **{}**: {}", term, self.text(definition))
+                }
+            })
+            .collect();
+        items.join("\n")
+    }
+
+    fn footnote(&mut self, text: &Text) -> String {
+        let text = self.text(text);
+        self.footnotes().push(text);
+        format!("[^{}]", self.footnotes().len())
+    }
+
+    fn header(&mut self, title: &Text, authors: &[Author]) -> String {
+        let title = format!("# {}", self.text(title));
+        if authors.is_empty() {
+            return title;
+        }
+        let authors: Vec<_> = authors.iter()
+            .map(|author| escape_markdown(&author.name))
+            .collect();
+        format!("{}\n\n{}", title, authors.join(", "))
+    }
+
+    fn image(&mut self, target: &str, alt: &str, title: Option<&Text>) -> String {
+        match title {
+            Some(title) => format!("![{}]({} \"{}\")", escape_markdown(alt), target,
+                title.plain_text().replace('"', "\\\"")),
+            None => format!("![{}]({})", escape_markdown(alt), target),
+        }
+    }
+
+    fn item(&mut self, item: &Item) -> String {
+        match *item {
+            Item::Anchor { ref id, .. } => format!("<a id=\"{}\"></a>", escape_html(id)),
+            Item::Button(ref label) | Item::Icon { name: ref label, .. } => escape_markdown(&format!("[{}]", label)),
+            Item::CrossReference { ref id, ref text } => {
+                let text =
+                    match *text {
+                        Some(ref text) => self.text(text),
+                        None => escape_markdown(id),
+                    };
+                format!("[{}](#{})", text, encode_link_target(id))
+            },
+            Item::Footnote(ref text) => self.footnote(text),
+            Item::InlineImage { ref alt, ref target } => format!("![{}]({})", escape_markdown(alt), target),
+            Item::Keyboard(ref keys) => {
+                let keys: Vec<_> = keys.iter()
+                    .map(|key| format!("<kbd>{}</kbd>", escape_markdown(key)))
+                    .collect();
+                keys.join("+")
+            },
+            Item::LineBreak => "\\\n".to_string(),
+            Item::Literal(ref text) => escape_markdown(text),
+            Item::Link { ref target, text: None, .. } => format!("<{}>", target),
+            Item::Link { ref target, text: Some(ref text), .. } => format!("[{}]({})", self.text(text), target),
+            Item::Mark(ref text, _) => format!("<mark>{}</mark>", self.text(text)),
+            Item::Menu { ref menu, ref path } => {
+                let mut menu = escape_markdown(menu);
+                for item in path {
+                    menu.push_str(" > ");
+                    menu.push_str(&escape_markdown(item));
+                }
+                format!("**{}**", menu)
+            },
+            Item::Passthrough(ref content) => content.clone(),
+            Item::Quoted { double, ref text } => {
+                let (open, close) = if double { ('“', '”') } else { ('‘', '’') };
+                format!("{}{}{}", open, self.text(text), close)
+            },
+            Item::Space => " ".to_string(),
+            Item::Tag(tag, ref text, _) => self.tag(tag, text),
+            Item::Word(ref word) => escape_markdown(&replace_characters(word)),
+        }
+    }
+
+    fn listing(&mut self, content: &str, language: Option<&str>, title: Option<&Text>) -> String {
+        let listing = code_block(content, language.unwrap_or(""));
+        self.titled(title, listing)
+    }
+
+    fn node(&mut self, node: &Node) -> String {
+        match *node {
+            Admonition { ref caption, kind, ref text, .. } => self.admonition(kind, caption, text),
+            AdmonitionBlock { ref caption, kind, ref nodes, .. } => self.admonition_block(kind, caption, nodes),
+            DescriptionList(ref items, _) => self.description_list(items),
+            Example { ref nodes, ref title, .. } | Sidebar { ref nodes, ref title, .. } => {
+                let blocks = self.nodes(nodes).join("\n\n");
+                self.titled(title.as_ref(), blocks)
+            },
+            Header { ref authors, ref title, .. } => self.header(title, authors),
+            HorizontalRule(..) => "---".to_string(),
+            Image { ref alt, ref target, ref title, .. } => self.image(target, alt, title.as_ref()),
+            Listing { ref content, ref language, ref title, .. } =>
+                self.listing(content, language.as_deref(), title.as_ref()),
+            Literal(ref content, _) => code_block(content, ""),
+            Open(ref nodes, _) => self.nodes(nodes).join("\n\n"),
+            OrderedList { ref items, start, .. } => self.ordered_list(items, start),
+            // Markdown has no page breaks.
+            PageBreak(_) => String::new(),
+            Paragraph(ref text, _, _) => self.paragraph(text),
+            Passthrough(ref content, _) => content.clone(),
+            Quote { ref author, ref content, ref source, .. } => self.quote(content, author.as_deref(), source.as_deref()),
+            Section { level, ref title, .. } => format!("{} {}", "#".repeat(level + 1), self.text(title)),
+            SubstitutedBlock { ref text, .. } => code_block(&text.plain_text(), ""),
+            Node::Table { ref rows, .. } => self.table(rows),
+            Toc(_) => String::new(),
+            UnorderedList(ref items, _, _) => self.unordered_list(items),
+        }
+    }
+
+    fn nodes(&mut self, nodes: &[Node]) -> Vec<String> {
+        nodes.iter()
+            .map(|node| self.node(node))
+            .filter(|block| !block.is_empty())
+            .collect()
+    }
+
+    fn ordered_list(&mut self, items: &[ListItem], start: Option<usize>) -> String {
+        let start = start.unwrap_or(1);
+        let mut lines = vec![];
+        for (index, item) in items.iter().enumerate() {
+            let marker = format!("{}. ", start + index);
+            lines.push(format!("{}{}", marker, self.text(&item.text)));
+            if let Some(ref nested) = item.nested {
+                lines.push(indent(&self.node(nested), marker.len()));
+            }
+        }
+        lines.join("\n")
+    }
+
+    fn paragraph(&mut self, text: &Text) -> String {
+        let text = self.text(text);
+        let lines: Vec<_> = text.split('\n')
+            .map(escape_line_start)
+            .collect();
+        lines.join("\n")
+    }
+
+    fn quote(&mut self, content: &[Node], author: Option<&str>, source: Option<&str>) -> String {
+        let mut blocks = self.nodes(content);
+        let attribution: Vec<_> = author.into_iter().chain(source)
+            .map(escape_markdown)
+            .collect();
+        if !attribution.is_empty() {
+            blocks.push(format!("— {}", attribution.join(", ")));
+        }
+        let lines: Vec<_> = blocks.join("\n\n").split('\n')
+            .map(|line| if line.is_empty() { ">".to_string() } else { format!("> {}", line) })
+            .collect();
+        lines.join("\n")
+    }
+
+    fn table(&mut self, rows: &[Vec<Text>]) -> String {
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let mut lines = vec![];
+        for (index, row) in rows.iter().enumerate() {
+            let mut cells: Vec<_> = row.iter()
+                .map(|cell| self.text(cell))
+                .collect();
+            cells.resize(columns, String::new());
+            lines.push(format!("| {} |", cells.join(" | ")));
+            if index == 0 {
+                lines.push(format!("|{}", " --- |".repeat(columns)));
+            }
+        }
+        lines.join("\n")
+    }
+
+    fn tag(&mut self, tag: Tag, text: &Text) -> String {
+        match tag {
+            Tag::Bold => format!("**{}**", self.text(text)),
+            Tag::InlineCode => code_span(&text.plain_text()),
+            Tag::Italic => format!("_{}_", self.text(text)),
+            Tag::SubScript => format!("<sub>{}</sub>", self.text(text)),
+            Tag::SuperScript => format!("<sup>{}</sup>", self.text(text)),
+        }
+    }
+
+    fn text(&mut self, text: &Text) -> String {
+        text.items.iter()
+            .map(|item| self.item(item))
+            .collect()
+    }
+
+    /// Prepend the `title` of a block, in bold, to its `content`.
+    fn titled(&mut self, title: Option<&Text>, content: String) -> String {
+        match title {
+            Some(title) => format!("**{}**\n\n{}", self.text(title), content),
+            None => content,
+        }
+    }
+
+    fn unordered_list(&mut self, items: &[ListItem]) -> String {
+        let mut lines = vec![];
+        for item in items {
+            lines.push(format!("- {}", self.text(&item.text)));
+            if let Some(ref nested) = item.nested {
+                lines.push(indent(&self.node(nested), 2));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+/// The default Markdown generator.
+#[derive(Default)]
+pub struct Generator {
+    footnotes: Vec<String>,
+}
+
+impl Generator {
+    /// Create a new generator.
+    pub fn new() -> Self {
+        Generator {
+            footnotes: vec![],
+        }
+    }
+}
+
+impl MarkdownGen for Generator {
+    fn footnotes(&mut self) -> &mut Vec<String> {
+        &mut self.footnotes
+    }
+}
+
+/// Create a fenced code block with the `content` and the `language` of the code, if not empty.
+/// The fence is longer than the runs of backquotes in the content.
+fn code_block(content: &str, language: &str) -> String {
+    let fence = "`".repeat((longest_backquote_run(content) + 1).max(3));
+    format!("{}{}\n{}\n{}", fence, language, content, fence)
+}
+
+/// Create a code span with the `content`, delimited by more backquotes than in the content.
+fn code_span(content: &str) -> String {
+    let fence = "`".repeat(longest_backquote_run(content) + 1);
+    if content.starts_with('`') || content.ends_with('`') {
+        format!("{} {} {}", fence, content, fence)
+    }
+    else {
+        format!("{}{}{}", fence, content, fence)
+    }
+}
+
+/// Percent-encode the characters of a link `target` which would end the destination of a
+/// Markdown link, like the spaces and the parentheses.
+fn encode_link_target(target: &str) -> String {
+    let mut encoded = String::with_capacity(target.len());
+    for character in target.chars() {
+        if character.is_ascii_control() || " \"()<>\\`".contains(character) {
+            encoded.push_str(&format!("%{:02X}", character as u32));
+        }
+        else {
+            encoded.push(character);
+        }
+    }
+    encoded
+}
+
+/// Escape the line of a paragraph when it starts with characters that would start a block, like
+/// `#` for a heading or `1.` for an ordered list.
+fn escape_line_start(line: &str) -> String {
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if line.starts_with(['#', '+', '-', '=']) {
+        format!("\\{}", line)
+    }
+    else if digits > 0 && line[digits..].starts_with(['.', ')']) {
+        format!("{}\\{}", &line[..digits], &line[digits..])
+    }
+    else {
+        line.to_string()
+    }
+}
+
+/// Escape the characters of the `text` which are formatting marks in Markdown, like `*`.
+pub fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        if ESCAPED_CHARS.contains(character) {
+            escaped.push('\\');
+        }
+        escaped.push(character);
+    }
+    escaped
+}
+
+/// Indent the lines of the `text` by `width` spaces.
+fn indent(text: &str, width: usize) -> String {
+    let indentation = " ".repeat(width);
+    let lines: Vec<_> = text.split('\n')
+        .map(|line| format!("{}{}", indentation, line))
+        .collect();
+    lines.join("\n")
+}
+
+/// Get the length of the longest run of backquotes in the `text`.
+fn longest_backquote_run(text: &str) -> usize {
+    text.split(|character| character != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Replace the characters like `(C)` by the character they represent, like `©`.
+fn replace_characters(text: &str) -> String {
+    let mut text = text.to_string();
+    for &(characters, _, number) in &REPLACEMENTS {
+        if let Some(character) = char::from_u32(number) {
+            text = text.replace(characters, &character.to_string());
+        }
+    }
+    text
+}
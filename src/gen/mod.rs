@@ -19,4 +19,219 @@
  * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
  */
 
+//! Generate documents from the asciidoctor nodes.
+
+use alloc::collections::BTreeSet;
+use alloc::fmt::format;
+use alloc::string::{String, ToString};
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io::Write;
+
+use error::Result;
+use node::{Attribute, Text};
+use self::html::escape_html;
+
+pub mod docbook;
 pub mod html;
+pub mod markdown;
+pub mod text;
+
+/// The replaced characters, like `(C)`, with the name and the number of their entity.
+const REPLACEMENTS: [(&str, &str, u32); 5] = [
+    ("(C)", "copy", 169),
+    ("(R)", "reg", 174),
+    ("(TM)", "trade", 8482),
+    ("--", "mdash", 8212),
+    ("...", "hellip", 8230),
+];
+
+/// An output where the generated document is written.
+/// With the `std` feature, it is implemented for every `std::io::Write`; `StringSink` collects
+/// the output in memory without requiring `std::io`.
+pub trait Sink {
+    /// Write the `text` to the output.
+    fn write_str(&mut self, text: &str) -> Result<()>;
+
+    /// Write formatted text to the output. This allows using the `write!` macro on a sink.
+    fn write_fmt(&mut self, arguments: fmt::Arguments) -> Result<()> {
+        self.write_str(&format(arguments))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Sink for W {
+    fn write_str(&mut self, text: &str) -> Result<()> {
+        self.write_all(text.as_bytes())?;
+        Ok(())
+    }
+
+    fn write_fmt(&mut self, arguments: fmt::Arguments) -> Result<()> {
+        Write::write_fmt(self, arguments)?;
+        Ok(())
+    }
+}
+
+/// A sink collecting the output in a `String`.
+#[derive(Debug, Default)]
+pub struct StringSink {
+    string: String,
+}
+
+impl StringSink {
+    /// Create a new empty sink.
+    pub fn new() -> Self {
+        StringSink {
+            string: String::new(),
+        }
+    }
+
+    /// Get the output collected so far.
+    pub fn as_str(&self) -> &str {
+        &self.string
+    }
+
+    /// Get the collected output, consuming the sink.
+    pub fn into_string(self) -> String {
+        self.string
+    }
+}
+
+impl Sink for StringSink {
+    fn write_str(&mut self, text: &str) -> Result<()> {
+        self.string.push_str(text);
+        Ok(())
+    }
+}
+
+/// Get the length of the character reference at the start of the text, like `&amp;`, `&#169;`
+/// or `&#xa9;`.
+fn character_reference_len(text: &str) -> Option<usize> {
+    let end = text.find(';')?;
+    let name = &text[1..end];
+    let valid =
+        if let Some(number) = name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+            !number.is_empty() && number.chars().all(|character| character.is_ascii_hexdigit())
+        }
+        else if let Some(number) = name.strip_prefix('#') {
+            !number.is_empty() && number.chars().all(|character| character.is_ascii_digit())
+        }
+        else {
+            name.starts_with(|character: char| character.is_ascii_alphabetic()) &&
+                name.chars().all(|character| character.is_ascii_alphanumeric())
+        };
+    if valid {
+        Some(end + 1)
+    }
+    else {
+        None
+    }
+}
+
+/// Get the entity of a character, named, like `&copy;`, or numeric, like `&#169;`.
+fn entity(name: &str, number: u32, named_entities: bool) -> String {
+    if named_entities {
+        format!("&{};", name)
+    }
+    else {
+        format!("&#{};", number)
+    }
+}
+
+/// Escape the text like escape_html(), except for the character references already written in
+/// the document, like `&amp;` or `&#169;`, which are kept as is, like asciidoctor does.
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(index) = rest.find('&') {
+        escaped.push_str(&escape_html(&rest[..index]));
+        rest = &rest[index..];
+        let length =
+            match character_reference_len(rest) {
+                Some(length) => {
+                    escaped.push_str(&rest[..length]);
+                    length
+                },
+                None => {
+                    escaped.push_str("&amp;");
+                    1
+                },
+            };
+        rest = &rest[length..];
+    }
+    escaped.push_str(&escape_html(rest));
+    escaped
+}
+
+/// Get the id in the `attributes`, like `[#id]`, if any.
+fn find_id_attribute(attributes: &[Attribute]) -> Option<String> {
+    for attribute in attributes {
+        if let Attribute::Id(ref id) = *attribute {
+            return Some(id.clone());
+        }
+    }
+    None
+}
+
+/// Check if the block `option`, like `%header`, is set in the `attributes`.
+fn has_option(attributes: &[Attribute], option: &str) -> bool {
+    attributes.iter()
+        .any(|attribute| matches!(*attribute, Attribute::Option(ref name) if name == option))
+}
+
+/// Escape the `text` like escape_text() and replace the characters like `(C)` by their entity,
+/// named or numeric.
+fn replace_characters(text: &str, named_entities: bool) -> String {
+    let mut replaced = String::new();
+    let mut rest = text;
+    loop {
+        let next_replacement = REPLACEMENTS.iter()
+            .filter_map(|replacement| rest.find(replacement.0).map(|index| (index, replacement)))
+            .min_by_key(|&(index, _)| index);
+        let (index, &(characters, name, number)) =
+            match next_replacement {
+                Some(replacement) => replacement,
+                None => break,
+            };
+        replaced.push_str(&escape_text(&rest[..index]));
+        replaced.push_str(&entity(name, number, named_entities));
+        rest = &rest[index + characters.len()..];
+    }
+    replaced.push_str(&escape_text(rest));
+    replaced
+}
+
+/// Generate the id of a section from its title, like `_section_title`.
+fn section_id(title: &Text) -> String {
+    let mut id = "_".to_string();
+    for character in title.plain_text().to_lowercase().chars() {
+        if character.is_alphanumeric() {
+            id.push(character);
+        }
+        else if !id.ends_with('_') {
+            id.push('_');
+        }
+    }
+    while id.len() > 1 && id.ends_with('_') {
+        id.pop();
+    }
+    id
+}
+
+/// Get the id of a section, from its `attributes` or generated from its `title`, with a suffix,
+/// like `_title_2`, when the generated id is already in `ids`. The id is then added to `ids`.
+fn unique_section_id(ids: &mut BTreeSet<String>, title: &Text, attributes: &[Attribute]) -> String {
+    if let Some(id) = find_id_attribute(attributes) {
+        ids.insert(id.clone());
+        return id;
+    }
+    let id = section_id(title);
+    let mut unique_id = id.clone();
+    let mut number = 2;
+    while ids.contains(&unique_id) {
+        unique_id = format!("{}_{}", id, number);
+        number += 1;
+    }
+    ids.insert(unique_id.clone());
+    unique_id
+}
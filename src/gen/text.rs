@@ -0,0 +1,211 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Generate plain text from the asciidoctor nodes, without any markup, like for search indexing.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use error::Result;
+use gen::Sink;
+use node::{AdmonitionKind, Author, ListItem, Node};
+use node::Node::*;
+use node::Text;
+
+/// The width of the line of dashes replacing the horizontal rules.
+const RULE_WIDTH: usize = 40;
+
+/// Write the resulting text for the specified `node`, followed by a newline, in the `writer`.
+pub fn gen<G: TextGen, W: Sink>(gen: &mut G, node: &Node, writer: &mut W) -> Result<()> {
+    let block = gen.node(node);
+    if !block.is_empty() {
+        writeln!(writer, "{}", block)?;
+    }
+    Ok(())
+}
+
+/// Write the text of the document made of the `nodes` in the `writer`, with the blocks separated
+/// by an empty line.
+pub fn gen_document<G: TextGen, W: Sink>(gen: &mut G, nodes: &[Node], writer: &mut W) -> Result<()> {
+    let blocks = gen.nodes(nodes);
+    if !blocks.is_empty() {
+        writeln!(writer, "{}", blocks.join("\n\n"))?;
+    }
+    Ok(())
+}
+
+/// Generate the text of an asciidoctor node.
+pub trait TextGen {
+    fn admonition(&mut self, _kind: AdmonitionKind, caption: &str, text: &Text) -> String {
+        format!("{}: {}", caption, self.text(text))
+    }
+
+    fn admonition_block(&mut self, _kind: AdmonitionKind, caption: &str, nodes: &[Node]) -> String {
+        let mut blocks = vec![format!("{}:", caption)];
+        blocks.append(&mut self.nodes(nodes));
+        blocks.join("\n\n")
+    }
+
+    fn description_list(&mut self, items: &[(Text, Text)]) -> String {
+        let items: Vec<_> = items.iter()
+            .map(|(term, definition)| {
+                let term = self.text(term);
+                if definition.items.is_empty() {
+                    term
+                }
+                else {
+                    format!("{}: {}", term, self.text(definition))
+                }
+            })
+            .collect();
+        items.join("\n")
+    }
+
+    fn header(&mut self, title: &Text, authors: &[Author]) -> String {
+        let title = self.text(title);
+        if authors.is_empty() {
+            return title;
+        }
+        let authors: Vec<_> = authors.iter()
+            .map(|author| author.name.as_str())
+            .collect();
+        format!("{}\n\n{}", title, authors.join(", "))
+    }
+
+    fn node(&mut self, node: &Node) -> String {
+        match *node {
+            Admonition { ref caption, kind, ref text, .. } => self.admonition(kind, caption, text),
+            AdmonitionBlock { ref caption, kind, ref nodes, .. } => self.admonition_block(kind, caption, nodes),
+            DescriptionList(ref items, _) => self.description_list(items),
+            Example { ref nodes, ref title, .. } | Sidebar { ref nodes, ref title, .. } => {
+                let mut blocks = vec![];
+                if let Some(ref title) = *title {
+                    blocks.push(self.text(title));
+                }
+                blocks.append(&mut self.nodes(nodes));
+                blocks.join("\n\n")
+            },
+            Header { ref authors, ref title, .. } => self.header(title, authors),
+            HorizontalRule(..) => "-".repeat(RULE_WIDTH),
+            Image { ref alt, ref title, .. } => {
+                match *title {
+                    Some(ref title) => format!("{}\n\n{}", alt, self.text(title)),
+                    None => alt.clone(),
+                }
+            },
+            Listing { ref content, ref title, .. } => {
+                match *title {
+                    Some(ref title) => format!("{}\n\n{}", self.text(title), content),
+                    None => content.clone(),
+                }
+            },
+            Literal(ref content, _) | Passthrough(ref content, _) => content.clone(),
+            Open(ref nodes, _) => self.nodes(nodes).join("\n\n"),
+            OrderedList { ref items, start, .. } => self.ordered_list(items, start),
+            PageBreak(_) => "\u{c}".to_string(),
+            Paragraph(ref text, _, _) | SubstitutedBlock { ref text, .. } => self.text(text),
+            Quote { ref author, ref content, ref source, .. } => self.quote(content, author.as_deref(), source.as_deref()),
+            Section { ref title, .. } => self.text(title),
+            Node::Table { ref rows, .. } => self.table(rows),
+            Toc(_) => String::new(),
+            UnorderedList(ref items, _, _) => self.unordered_list(items),
+        }
+    }
+
+    fn nodes(&mut self, nodes: &[Node]) -> Vec<String> {
+        nodes.iter()
+            .map(|node| self.node(node))
+            .filter(|block| !block.is_empty())
+            .collect()
+    }
+
+    fn ordered_list(&mut self, items: &[ListItem], start: Option<usize>) -> String {
+        let start = start.unwrap_or(1);
+        let mut lines = vec![];
+        for (index, item) in items.iter().enumerate() {
+            let marker = format!("{}. ", start + index);
+            lines.push(format!("{}{}", marker, self.text(&item.text)));
+            if let Some(ref nested) = item.nested {
+                lines.push(indent(&self.node(nested), marker.len()));
+            }
+        }
+        lines.join("\n")
+    }
+
+    fn quote(&mut self, content: &[Node], author: Option<&str>, source: Option<&str>) -> String {
+        let mut blocks = self.nodes(content);
+        let attribution: Vec<_> = author.into_iter().chain(source).collect();
+        if !attribution.is_empty() {
+            blocks.push(format!("— {}", attribution.join(", ")));
+        }
+        blocks.join("\n\n")
+    }
+
+    fn table(&mut self, rows: &[Vec<Text>]) -> String {
+        let rows: Vec<_> = rows.iter()
+            .map(|row| {
+                let cells: Vec<_> = row.iter()
+                    .map(|cell| self.text(cell))
+                    .collect();
+                cells.join("\t")
+            })
+            .collect();
+        rows.join("\n")
+    }
+
+    fn text(&mut self, text: &Text) -> String {
+        text.plain_text()
+    }
+
+    fn unordered_list(&mut self, items: &[ListItem]) -> String {
+        let mut lines = vec![];
+        for item in items {
+            lines.push(format!("- {}", self.text(&item.text)));
+            if let Some(ref nested) = item.nested {
+                lines.push(indent(&self.node(nested), 2));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+/// The default text generator.
+#[derive(Default)]
+pub struct Generator;
+
+impl Generator {
+    /// Create a new generator.
+    pub fn new() -> Self {
+        Generator
+    }
+}
+
+impl TextGen for Generator {
+}
+
+/// Indent the lines of the `text` by `width` spaces.
+fn indent(text: &str, width: usize) -> String {
+    let indentation = " ".repeat(width);
+    let lines: Vec<_> = text.split('\n')
+        .map(|line| format!("{}{}", indentation, line))
+        .collect();
+    lines.join("\n")
+}
@@ -0,0 +1,186 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Replace the include directives, like `include::chapter.adoc[]`, by the included content.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use error::{Error, Result};
+use lexer::Source;
+
+const DEFAULT_MAX_DEPTH: usize = 64;
+
+/// Get the content of the target of an include directive.
+pub trait Resolver {
+    fn resolve(&mut self, target: &str) -> Result<Vec<u8>>;
+}
+
+impl<F: FnMut(&str) -> Result<Vec<u8>>> Resolver for F {
+    fn resolve(&mut self, target: &str) -> Result<Vec<u8>> {
+        self(target)
+    }
+}
+
+/// An included document being read.
+struct Included {
+    content: Vec<u8>,
+    index: usize,
+    /// The offset added to the levels of the sections of this document, including the offsets of
+    /// the documents including it.
+    level_offset: isize,
+}
+
+/// A source replacing the include directives by the content returned by the resolver.
+/// The included content can itself contain include directives.
+pub struct Includes<S: Source, R: Resolver> {
+    included: Vec<Included>,
+    line: Vec<u8>,
+    max_depth: usize,
+    resolver: R,
+    source: S,
+    source_index: usize,
+}
+
+impl<S: Source, R: Resolver> Includes<S, R> {
+    /// Create a new source resolving the includes of the `source` with the `resolver`.
+    pub fn new(source: S, resolver: R) -> Self {
+        Self::with_max_depth(source, resolver, DEFAULT_MAX_DEPTH)
+    }
+
+    /// Create a new source resolving the includes of the `source` with the `resolver`.
+    /// Nesting the includes deeper than `max_depth` returns an error, which also stops cyclic
+    /// includes.
+    pub fn with_max_depth(source: S, resolver: R, max_depth: usize) -> Self {
+        Includes {
+            included: vec![],
+            line: vec![],
+            max_depth,
+            resolver,
+            source,
+            source_index: 0,
+        }
+    }
+
+    /// Get the next line, including its newline, from the innermost included document.
+    fn next_line(&mut self) -> Result<Option<Vec<u8>>> {
+        while let Some(included) = self.included.last_mut() {
+            if included.index >= included.content.len() {
+                self.included.pop();
+                continue;
+            }
+            let rest = &included.content[included.index..];
+            let end = rest.iter().position(|&byte| byte == b'\n').map_or(rest.len(), |index| index + 1);
+            included.index += end;
+            return Ok(Some(rest[..end].to_vec()));
+        }
+
+        let mut line = vec![];
+        loop {
+            if self.source_index >= self.source.bytes().len() {
+                if !self.source.fill()? {
+                    break;
+                }
+                self.source_index = 0;
+            }
+            let byte = self.source.bytes()[self.source_index];
+            self.source_index += 1;
+            line.push(byte);
+            if byte == b'\n' {
+                break;
+            }
+        }
+        if line.is_empty() {
+            Ok(None)
+        }
+        else {
+            Ok(Some(line))
+        }
+    }
+}
+
+impl<S: Source, R: Resolver> Source for Includes<S, R> {
+    fn bytes(&self) -> &[u8] {
+        &self.line
+    }
+
+    fn fill(&mut self) -> Result<bool> {
+        loop {
+            let line =
+                match self.next_line()? {
+                    Some(line) => line,
+                    None => return Ok(false),
+                };
+            if let Some((target, level_offset)) = include_directive(&line) {
+                if self.included.len() >= self.max_depth {
+                    return Err(Error::IncludeDepth {
+                        max_depth: self.max_depth,
+                        target,
+                    });
+                }
+                let mut content = self.resolver.resolve(&target)?;
+                if !content.is_empty() && !content.ends_with(b"\n") {
+                    content.push(b'\n');
+                }
+                let parent_offset = self.included.last().map_or(0, |included| included.level_offset);
+                let level_offset =
+                    match level_offset {
+                        // The parser shifts the sections with the `leveloffset` attribute, which is
+                        // restored at the end of the included content.
+                        Some(offset) => {
+                            let offset = parent_offset + offset;
+                            let mut with_offset = format!(":leveloffset: {}\n", offset).into_bytes();
+                            with_offset.append(&mut content);
+                            with_offset.extend(format!("\n:leveloffset: {}\n", parent_offset).bytes());
+                            content = with_offset;
+                            offset
+                        },
+                        None => parent_offset,
+                    };
+                self.included.push(Included {
+                    content,
+                    index: 0,
+                    level_offset,
+                });
+                continue;
+            }
+            self.line = line;
+            return Ok(true);
+        }
+    }
+}
+
+/// Get the target of the include directive on this `line`, if any, with its level offset, like
+/// `include::chapter.adoc[leveloffset=+1]`.
+fn include_directive(line: &[u8]) -> Option<(String, Option<isize>)> {
+    let mut line = line;
+    while let Some((b'\n', rest)) | Some((b'\r', rest)) = line.split_last() {
+        line = rest;
+    }
+    let directive = line.strip_prefix(b"include::")?.strip_suffix(b"]")?;
+    let end = directive.iter().position(|&byte| byte == b'[')?;
+    let target = String::from_utf8_lossy(&directive[..end]).into_owned();
+    let level_offset = directive[end + 1..].split(|&byte| byte == b',')
+        .filter_map(|attribute| attribute.trim_ascii().strip_prefix(b"leveloffset="))
+        .filter_map(|value| core::str::from_utf8(value).ok()?.parse().ok())
+        .next();
+    Some((target, level_offset))
+}
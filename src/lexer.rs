@@ -21,7 +21,11 @@
 
 //! Return the tokens from an asciidoctor text.
 
-use std::char;
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::char;
+#[cfg(feature = "std")]
 use std::io::Read;
 
 use error::Error;
@@ -43,7 +47,7 @@ macro_rules! lex1_or_2 {
     ($func_name:ident, $char:expr, $token:ident, $token2:ident) => {
         fn $func_name(&mut self) -> Result<Token> {
             self.eat($char)?;
-            if self.current_char()? == $char {
+            if self.is_current_char($char)? {
                 self.eat($char)?;
                 Ok($token2)
             } else {
@@ -53,41 +57,130 @@ macro_rules! lex1_or_2 {
     };
 }
 
+#[cfg(feature = "std")]
 const BUFFER_SIZE: usize = 4096;
 
+/// The characters ending a word token, because they are significant: the spaces, the formatting
+/// marks and the characters starting the other tokens, like `{` for an attribute reference.
+const WORD_TERMINATORS: &[u8] = b" *_`#[]{}^~:<>+|\\\n\r\t";
+
+/// The characters made literal when preceded by a backslash.
+const ESCAPABLE_CHARS: &[u8] = b"#*[\\]^_`{~";
+
+/// The schemes starting the URLs which are recognized in the text.
+const URL_SCHEMES: [&[u8]; 4] = [b"ftp://", b"http://", b"https://", b"mailto:"];
+
 struct NextToken {
     previous_pos: Pos,
+    /// The bytes of the source read for this token, to read them again when rewinding.
+    raw: Vec<u8>,
     token: Token,
 }
 
-pub struct Lexer<R: Read> {
+/// A source of bytes for the lexer.
+pub trait Source {
+    /// Get the bytes that are currently available.
+    fn bytes(&self) -> &[u8];
+
+    /// Replace the available bytes by the next bytes of the source.
+    /// Return `false` at the end of the source.
+    fn fill(&mut self) -> Result<bool>;
+}
+
+/// A source reading from a `Read` through a fixed-size buffer, which is suitable for streaming.
+#[cfg(feature = "std")]
+pub struct Reader<R: Read> {
     buffer: [u8; BUFFER_SIZE],
+    reader: R,
+    size: usize,
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> Source for Reader<R> {
+    fn bytes(&self) -> &[u8] {
+        &self.buffer[..self.size]
+    }
+
+    fn fill(&mut self) -> Result<bool> {
+        self.size = self.reader.read(&mut self.buffer)?;
+        Ok(self.size > 0)
+    }
+}
+
+/// An in-memory source, which is indexed directly by the lexer.
+impl Source for &[u8] {
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+
+    fn fill(&mut self) -> Result<bool> {
+        // All the bytes are available from the start.
+        Ok(false)
+    }
+}
+
+pub struct Lexer<R: Source> {
     buffer_index: usize,
-    buffer_size: usize,
+    /// The bytes kept from the previous fills of the source, before the bytes of the source, to
+    /// look ahead across the end of the buffer.
+    carry: Vec<u8>,
     column: usize,
     line: usize,
-    next_token: Option<NextToken>,
-    reader: R,
+    next_tokens: VecDeque<NextToken>,
+    /// The bytes read for the token being peeked.
+    raw: Option<Vec<u8>>,
+    source: R,
+    /// The index of the first byte of the source after the bytes kept in `carry`.
+    source_start: usize,
 }
 
-impl<R: Read> Lexer<R> {
-    /// Create a new parser from a `Reader`.
+#[cfg(feature = "std")]
+impl<R: Read> Lexer<Reader<R>> {
+    /// Create a new lexer from a `Reader`.
     /// This is an iterator over the tokens.
     pub fn new(reader: R) -> Self {
-        Lexer {
+        Self::with_source(Reader {
             buffer: [0; BUFFER_SIZE],
-            buffer_index: BUFFER_SIZE,
-            buffer_size: 0,
-            column: 1,
-            line: 1,
-            next_token: None,
             reader,
+            size: 0,
+        })
+    }
+}
+
+impl<'a> Lexer<&'a [u8]> {
+    /// Create a new lexer reading the `bytes` directly, without copying them in a buffer.
+    pub fn from_bytes(bytes: &'a [u8]) -> Self {
+        Self::with_source(bytes)
+    }
+}
+
+impl<R: Source> Lexer<R> {
+    /// Create a new lexer from a `Source`.
+    pub fn with_source(source: R) -> Self {
+        Self::with_position(source, Pos::new(1, 1))
+    }
+
+    /// Create a new lexer from a `Source` which starts at the position `pos` of the document,
+    /// like when lexing the rest of a document from a line.
+    pub fn with_position(source: R, pos: Pos) -> Self {
+        Lexer {
+            buffer_index: 0,
+            carry: vec![],
+            column: pos.column,
+            line: pos.line,
+            next_tokens: VecDeque::new(),
+            raw: None,
+            source,
+            source_start: 0,
         }
     }
 
     /// Advance the internal position cursor.
     fn advance(&mut self, actual: u8) {
         self.buffer_index += 1;
+        if let Some(ref mut raw) = self.raw {
+            raw.push(actual);
+        }
         if actual == b'\n' {
             self.line += 1;
             self.column = 1;
@@ -117,46 +210,143 @@ impl<R: Read> Lexer<R> {
 
     lex1_or_2!(backquote, b'`', Backquote, DoubleBackquote);
     lex!(caret, b'^', Caret);
+    lex!(close_curly_bracket, b'}', CloseCurlyBracket);
     lex!(close_square_bracket, b']', CloseSquareBracket);
+    lex1_or_2!(colon, b':', Colon, DoubleColon);
     lex!(newline, b'\n', NewLine);
     lex!(number_sign, b'#', NumberSign);
+    lex!(open_curly_bracket, b'{', OpenCurlyBracket);
     lex!(open_square_bracket, b'[', OpenSquareBracket);
+    lex!(pipe, b'|', Pipe);
     lex!(space, b' ', Space);
     lex1_or_2!(star, b'*', Star, DoubleStar);
     lex!(tilde, b'~', Tilde);
     lex1_or_2!(underscore, b'_', Underscore, DoubleUnderscore);
 
-    /// Parse (and ignore) a comment.
-    fn comment(&mut self) -> Result<()> {
-        self.eat(b'/')?;
-        self.eat(b'/')?;
+    /// Parse a backslash, which makes the following square bracket, formatting mark or backslash
+    /// literal.
+    fn backslash(&mut self) -> Result<Token> {
+        self.eat(b'\\')?;
+        match self.lookahead(0)? {
+            Some(actual) if ESCAPABLE_CHARS.contains(&actual) => {
+                self.advance(actual);
+                Ok(Word(vec![actual]))
+            },
+            // A backslash at the end of the document is literal too.
+            _ => Ok(Word(vec![b'\\'])),
+        }
+    }
 
-        // Try to parse a multiline comment.
-        if self.current_char()? == b'/' {
-            self.eat(b'/')?;
-            self.eat(b'/')?;
+    /// Get the available byte at `index`, which is either kept from the previous fills or from the
+    /// source.
+    fn byte_at(&self, index: usize) -> u8 {
+        match self.carry.get(index) {
+            Some(&byte) => byte,
+            None => self.source.bytes()[self.source_start + index - self.carry.len()],
+        }
+    }
 
-            let comment_delim = b"////";
-            while &self.buffer[self.buffer_index..self.buffer_index + comment_delim.len()] != comment_delim {
-                self.advance_to_eol()?;
-                self.advance_while(|c| c == b'\n')?;
+    /// Parse the end of a curved quote, `` `" `` or `` `' ``.
+    fn close_quote(&mut self) -> Result<Token> {
+        self.eat(b'`')?;
+        let actual = self.current_char()?;
+        self.advance(actual);
+        if actual == b'"' {
+            Ok(CloseDoubleQuote)
+        }
+        else {
+            Ok(CloseSingleQuote)
+        }
+    }
+
+    /// Parse (and ignore) a comment at the start of a line, like `// comment`, or a comment block
+    /// delimited by `////`, and return the next token.
+    /// A single `/` is the start of a word.
+    fn comment(&mut self) -> Result<Token> {
+        let mut count = 0;
+        while self.is_current_char(b'/')? {
+            self.eat(b'/')?;
+            count += 1;
+            if count == 4 && matches!(self.current_char(), Ok(b'\n') | Ok(b'\r') | Err(Error::Eof)) {
+                self.comment_block()?;
+                return self.read_token();
             }
-            // Eat the //// token.
-            for _ in 0..4 {
-                self.eat(b'/')?;
+        }
+        if count == 1 {
+            let mut word = vec![b'/'];
+            if matches!(self.lookahead(0)?, Some(actual) if !is_word_terminator(actual)) {
+                if let Word(rest) = self.word()? {
+                    word.extend(rest);
+                }
             }
+            return Ok(Word(word));
         }
-        else {
-            // Single comment.
-            self.advance_to_eol()?;
+        self.advance_to_eol()?;
+        self.read_token()
+    }
+
+    /// Skip the content of a comment block until its closing `////` line.
+    /// The lines are read character by character, since the closing line can be split between
+    /// two fills of the buffer. An unterminated comment block returns an `Eof` error.
+    fn comment_block(&mut self) -> Result<()> {
+        // The number of `/` at the start of the current line, if the line only contains `/` so far.
+        let mut slashes = None;
+        loop {
+            let actual = self.current_char()?;
+            slashes =
+                match actual {
+                    b'\n' => Some(0),
+                    b'/' => slashes.map(|count| count + 1),
+                    _ => None,
+                };
+            self.advance(actual);
+            if slashes == Some(4) && matches!(self.current_char(), Ok(b'\n') | Ok(b'\r') | Err(Error::Eof)) {
+                return Ok(());
+            }
         }
-        Ok(())
     }
 
     /// Get the current character (filling the buffer if needed).
     fn current_char(&mut self) -> Result<u8> {
         self.read_if_needed()?;
-        Ok(self.buffer[self.buffer_index])
+        Ok(self.byte_at(self.buffer_index))
+    }
+
+    /// Parse a line of four or more `delimiter`, like `----`, which delimits a block.
+    /// Otherwise, the delimiters are the start of a word.
+    fn delimiter_line(&mut self, delimiter: u8) -> Result<Token> {
+        let mut count = 0;
+        while self.is_current_char(delimiter)? {
+            self.eat(delimiter)?;
+            count += 1;
+        }
+        let actual = self.lookahead(0)?;
+        // A line of exactly two `-`, `--`, delimits an open block.
+        let is_delimiter = count >= 4 || delimiter == b'-' && count == 2;
+        if is_delimiter && matches!(actual, None | Some(b'\n') | Some(b'\r')) {
+            return Ok(DelimiterLine(delimiter, count));
+        }
+        let mut word = vec![delimiter; count];
+        if matches!(actual, Some(actual) if !is_word_terminator(actual)) {
+            if let Word(rest) = self.word()? {
+                word.extend(rest);
+            }
+        }
+        Ok(Word(word))
+    }
+
+    /// Parse a run of `.` at the start of a line, like the `..` marker of an ordered list item.
+    /// A line of four or more `.`, like `....`, delimits a literal block.
+    fn dot_run(&mut self) -> Result<Token> {
+        let mut count = 0;
+        while self.is_current_char(b'.')? {
+            self.eat(b'.')?;
+            count += 1;
+        }
+        if count >= 4 && matches!(self.current_char(), Ok(b'\n') | Ok(b'\r') | Err(Error::Eof)) {
+            return Ok(DelimiterLine(b'.', count));
+        }
+        Ok(DotRun(count))
     }
 
     /// Eat the next character if it is the one specified in the parameter.
@@ -176,23 +366,191 @@ impl<R: Read> Lexer<R> {
         }
     }
 
+    /// Parse a run of = at the start of a line.
+    /// A line of four or more =, like `====`, delimits an example block.
+    fn equals_run(&mut self) -> Result<Token> {
+        let mut count = 0;
+        while self.is_current_char(b'=')? {
+            self.eat(b'=')?;
+            count += 1;
+        }
+        if count >= 4 && matches!(self.current_char(), Ok(b'\n') | Ok(b'\r') | Err(Error::Eof)) {
+            return Ok(DelimiterLine(b'=', count));
+        }
+        Ok(EqualsRun(count))
+    }
+
+    /// Parse >>, or a single > as a word.
+    fn greater_than(&mut self) -> Result<Token> {
+        self.eat(b'>')?;
+        if self.is_current_char(b'>')? {
+            self.eat(b'>')?;
+            Ok(DoubleGt)
+        }
+        else {
+            Ok(Word(vec![b'>']))
+        }
+    }
+
+    /// Parse three < (page break) or two < (cross reference), or a single < as a word.
+    fn less_than(&mut self) -> Result<Token> {
+        self.eat(b'<')?;
+        if !self.is_current_char(b'<')? {
+            return Ok(Word(vec![b'<']));
+        }
+        self.eat(b'<')?;
+        if self.is_current_char(b'<')? {
+            self.eat(b'<')?;
+            Ok(TripleLt)
+        }
+        else {
+            Ok(DoubleLt)
+        }
+    }
+
+    /// Get the next character without consuming it.
+    /// The peeked tokens are put back, so this is the first character of the next token.
+    pub fn peek_char(&mut self) -> Result<u8> {
+        self.rewind();
+        self.current_char()
+    }
+
+    /// Check if the current character is `character`, which is false at the end of the source.
+    fn is_current_char(&mut self, character: u8) -> Result<bool> {
+        Ok(self.lookahead(0)? == Some(character))
+    }
+
+    /// Check if the next characters are a line of four or more `delimiter`, like `****`, which
+    /// delimits a block. This is used for the characters which are formatting marks otherwise.
+    fn is_delimiter_line(&mut self, delimiter: u8) -> Result<bool> {
+        let mut count = 0;
+        while self.lookahead(count)? == Some(delimiter) {
+            count += 1;
+        }
+        Ok(count >= 4 && matches!(self.lookahead(count)?, None | Some(b'\n') | Some(b'\r')))
+    }
+
+    /// Check if the next characters are `bytes`.
+    fn is_next_bytes(&mut self, bytes: &[u8]) -> Result<bool> {
+        for (index, &byte) in bytes.iter().enumerate() {
+            if self.lookahead(index)? != Some(byte) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Check if the character after the current one is `character`.
+    fn is_next_char(&mut self, character: u8) -> Result<bool> {
+        Ok(self.lookahead(1)? == Some(character))
+    }
+
+    /// Check if a URL, like `https://example.com`, starts at the current character.
+    fn is_url(&mut self) -> Result<bool> {
+        for scheme in &URL_SCHEMES {
+            if self.is_next_bytes(scheme)? && self.lookahead(scheme.len())?.is_some_and(|byte| !is_url_terminator(byte)) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Get the number of available bytes.
+    fn len(&self) -> usize {
+        self.carry.len() + self.source.bytes().len() - self.source_start
+    }
+
+    /// Get the character at `index` after the current one without consuming it, or `None` at the
+    /// end of the source.
+    /// The remaining bytes are kept when the source is filled, so that the lookahead can cross the
+    /// end of the buffer.
+    fn lookahead(&mut self, index: usize) -> Result<Option<u8>> {
+        while self.buffer_index + index >= self.len() {
+            let rest = (self.buffer_index..self.len())
+                .map(|index| self.byte_at(index))
+                .collect();
+            let filled = self.source.fill()?;
+            // An in-memory source keeps its bytes at the end, otherwise they are replaced.
+            if filled || self.source.bytes().is_empty() {
+                self.carry = rest;
+                self.buffer_index = 0;
+                self.source_start = 0;
+            }
+            if !filled {
+                return Ok(None);
+            }
+        }
+        Ok(Some(self.byte_at(self.buffer_index + index)))
+    }
+
     /// Peek to get the next token. This token will be returned by the next call to token().
     pub fn peek(&mut self) -> Result<&Token> {
-        if self.next_token.is_none() {
-            let previous_pos = self.pos();
-            self.next_token = Some(NextToken {
-                token: self.token()?,
+        self.peek_nth(0)
+    }
+
+    /// Peek to get the token at the specified `index` after the current position, without
+    /// consuming any token. The index 0 is the next token.
+    pub fn peek_nth(&mut self, index: usize) -> Result<&Token> {
+        while self.next_tokens.len() <= index {
+            let previous_pos = Pos::new(self.line, self.column);
+            self.raw = Some(vec![]);
+            let token = self.read_token();
+            let raw = self.raw.take().unwrap_or_default();
+            self.next_tokens.push_back(NextToken {
                 previous_pos,
+                raw,
+                token: token?,
             });
         }
-        // The next_token attribute is assigned a Some value if it is None, so unwrap() always
-        // works.
-        Ok(&self.next_token.as_ref().unwrap().token)
+        Ok(&self.next_tokens[index].token)
+    }
+
+    /// Parse a word starting with (, which is a word by itself when a URL follows it, like in
+    /// `(https://example.com)`.
+    fn open_parenthesis(&mut self) -> Result<Token> {
+        self.eat(b'(')?;
+        let mut word = vec![b'('];
+        if !self.is_url()? && matches!(self.current_char(), Ok(actual) if !is_word_terminator(actual)) {
+            if let Word(rest) = self.word()? {
+                word.extend(rest);
+            }
+        }
+        Ok(Word(word))
+    }
+
+    /// Parse the start of a curved quote, `` "` `` or `` '` ``, starting with the `quote`.
+    fn open_quote(&mut self, quote: u8) -> Result<Token> {
+        self.eat(quote)?;
+        self.eat(b'`')?;
+        if quote == b'"' {
+            Ok(OpenDoubleQuote)
+        }
+        else {
+            Ok(OpenSingleQuote)
+        }
+    }
+
+    /// Parse a single + (line break), three + (passthrough), or other + as a word.
+    fn plus(&mut self) -> Result<Token> {
+        let mut count = 0;
+        while self.is_current_char(b'+')? {
+            self.eat(b'+')?;
+            count += 1;
+        }
+        if count == 1 {
+            Ok(Plus)
+        }
+        else if count == 3 {
+            Ok(TriplePlus)
+        }
+        else {
+            Ok(Word(vec![b'+'; count]))
+        }
     }
 
     /// Get the current position in the file.
     pub fn pos(&self) -> Pos {
-        if let Some(ref token) = self.next_token {
+        if let Some(token) = self.next_tokens.front() {
             token.previous_pos
         }
         else {
@@ -200,11 +558,39 @@ impl<R: Read> Lexer<R> {
         }
     }
 
-    /// Read from the buffer if needed.
+    /// Read the next line as is, without tokenizing it. The newline is not included.
+    /// The peeked tokens are put back, so the line starts at the next token.
+    pub fn raw_line(&mut self) -> Result<Vec<u8>> {
+        self.rewind();
+        let mut line = vec![];
+        loop {
+            match self.current_char() {
+                Ok(b'\n') => {
+                    self.advance(b'\n');
+                    break;
+                },
+                Ok(actual) => {
+                    self.advance(actual);
+                    line.push(actual);
+                },
+                Err(Error::Eof) if !line.is_empty() => break,
+                Err(error) => return Err(error),
+            }
+        }
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        Ok(line)
+    }
+
+    /// Read from the source if needed.
     fn read_if_needed(&mut self) -> Result<()> {
-        if self.buffer_index >= self.buffer_size {
-            self.buffer_size = self.reader.read(&mut self.buffer)?;
-            if self.buffer_size == 0 {
+        if self.buffer_index >= self.len() {
+            self.carry.clear();
+            self.source_start = 0;
+            if !self.source.fill()? {
+                // All the bytes are consumed.
+                self.buffer_index = self.len();
                 return Err(Error::Eof);
             }
             self.buffer_index = 0;
@@ -212,38 +598,78 @@ impl<R: Read> Lexer<R> {
         Ok(())
     }
 
-    /// Get the next token from the file.
-    pub fn token(&mut self) -> Result<Token> {
-        if let Some(token) = self.next_token.take() {
-            return Ok(token.token);
-        }
+    /// Read the next token from the file.
+    fn read_token(&mut self) -> Result<Token> {
         self.read_if_needed()?;
         let actual = self.current_char()?;
         match actual {
-            b'/' => {
-                self.comment()?;
-                self.token()
-            },
-            b'<' => self.triple_lt(),
-            b'\'' => self.triple_apos(),
+            b'/' if self.column == 1 => self.comment(),
+            b'=' if self.column == 1 => self.equals_run(),
+            b'.' if self.column == 1 => self.dot_run(),
+            b'-' if self.column == 1 => self.delimiter_line(b'-'),
+            b'*' | b'+' | b'_' if self.column == 1 && self.is_delimiter_line(actual)? => self.delimiter_line(actual),
+            b'<' => self.less_than(),
+            b'>' => self.greater_than(),
+            b'+' => self.plus(),
+            b'|' => self.pipe(),
+            b'"' | b'\'' if self.is_next_char(b'`')? => self.open_quote(actual),
+            b'`' if self.is_next_char(b'"')? || self.is_next_char(b'\'')? => self.close_quote(),
+            b'\'' if self.is_next_bytes(b"'''")? => self.triple_apos(),
             b'\n' => self.newline(),
             b'\r' => {
                 self.advance(actual);
-                self.token()
+                self.read_token()
             },
             b'#' => self.number_sign(),
             b' ' => self.space(),
+            b'\\' => self.backslash(),
             b'[' => self.open_square_bracket(),
             b']' => self.close_square_bracket(),
+            b'{' => self.open_curly_bracket(),
+            b'(' => self.open_parenthesis(),
+            b'}' => self.close_curly_bracket(),
+            b':' => self.colon(),
             b'_' => self.underscore(),
             b'*' => self.star(),
             b'`' => self.backquote(),
             b'^' => self.caret(),
             b'~' => self.tilde(),
+            _ if self.is_url()? => self.url(),
             _ => self.word(),
         }
     }
 
+    /// Put back the bytes of the peeked tokens in front of the remaining bytes, to read them
+    /// again from the position of the first peeked token.
+    fn rewind(&mut self) {
+        let previous_pos =
+            match self.next_tokens.front() {
+                Some(token) => token.previous_pos,
+                None => return,
+            };
+        let mut bytes: Vec<u8> = self.next_tokens.drain(..)
+            .flat_map(|token| token.raw)
+            .collect();
+        if self.buffer_index < self.carry.len() {
+            bytes.extend_from_slice(&self.carry[self.buffer_index..]);
+        }
+        else {
+            self.source_start += self.buffer_index - self.carry.len();
+        }
+        self.carry = bytes;
+        self.buffer_index = 0;
+        self.line = previous_pos.line;
+        self.column = previous_pos.column;
+    }
+
+    /// Get the next token from the file.
+    pub fn token(&mut self) -> Result<Token> {
+        if let Some(token) = self.next_tokens.pop_front() {
+            return Ok(token.token);
+        }
+        self.read_token()
+    }
+
     /// Parse three '.
     fn triple_apos(&mut self) -> Result<Token> {
         self.eat(b'\'')?;
@@ -252,23 +678,57 @@ impl<R: Read> Lexer<R> {
         Ok(TripleApos)
     }
 
-    /// Parse three <.
-    fn triple_lt(&mut self) -> Result<Token> {
-        self.eat(b'<')?;
-        self.eat(b'<')?;
-        self.eat(b'<')?;
-        Ok(TripleLt)
+    /// Parse a URL, like `https://example.com`. The punctuation at the end, like the period
+    /// ending a sentence, is not part of the URL.
+    fn url(&mut self) -> Result<Token> {
+        let mut url = vec![];
+        while let Some(byte) = self.lookahead(url.len())? {
+            if is_url_terminator(byte) {
+                break;
+            }
+            url.push(byte);
+        }
+        while url.last().is_some_and(|byte| b".,;:!?)".contains(byte)) {
+            url.pop();
+        }
+        for &byte in &url {
+            self.advance(byte);
+        }
+        Ok(Url(String::from_utf8(url)?))
     }
 
     /// Parse a word.
+    /// The bytes are accumulated, since the word can continue after the buffer is refilled, even
+    /// in the middle of a multibyte character.
     fn word(&mut self) -> Result<Token> {
-        let start_index = self.buffer_index;
-        self.advance_while(|c| !b" *_`#[]^~:\n\r\t".contains(&c))?;
-        if self.buffer_index == start_index {
+        let mut word = vec![];
+        loop {
+            match self.current_char() {
+                // The `#` of a numeric character reference, like `&#169;`, is part of the word.
+                Ok(actual) if !is_word_terminator(actual) || actual == b'#' && word.last() == Some(&b'&') => {
+                    self.advance(actual);
+                    word.push(actual);
+                },
+                Ok(_) => break,
+                Err(Error::Eof) if !word.is_empty() => break,
+                Err(error) => return Err(error),
+            }
+        }
+        if word.is_empty() {
             return Err(Error::Msg(format!("bug in the lexer, next character `{}` is not part of a word token",
                   char::from_u32(self.current_char()? as u32)
                       .ok_or("byte is not a character")?)));
         }
-        Ok(Word(self.buffer[start_index..self.buffer_index].to_vec()))
+        Ok(Word(word))
     }
 }
+
+/// Check if the character ends a URL.
+fn is_url_terminator(character: u8) -> bool {
+    b" []<>\"\n\r\t".contains(&character)
+}
+
+/// Check if the character ends a word token.
+fn is_word_terminator(character: u8) -> bool {
+    WORD_TERMINATORS.contains(&character)
+}
@@ -20,25 +20,84 @@
  */
 
 //! Crate to parse asciidoctor and convert it to HTML.
+//!
+//! The parser and the generators only need `alloc`: disable the default `std` feature to use
+//! this crate in a `no_std` environment. The lexer then reads from byte slices, with
+//! `Lexer::from_bytes()`.
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
+extern crate core;
+#[macro_use]
+extern crate alloc;
+
+mod document;
 mod error;
+mod event;
 mod gen;
+mod include;
 mod lexer;
 mod node;
 mod parser;
 mod position;
 mod token;
 
-//use std::io::{Read, Write};
+#[cfg(feature = "std")]
+use std::io::{Read, Write};
 
+#[cfg(feature = "std")]
+use gen::html::Generator;
+
+pub use document::{Document, OutlineEntry};
 pub use error::{Error, Result};
-pub use gen::html;
-pub use lexer::Lexer;
-pub use node::Node;
+pub use event::{Event, Events};
+pub use gen::{docbook, html, markdown, text, Sink, StringSink};
+pub use include::{Includes, Resolver};
+#[cfg(feature = "std")]
+pub use lexer::Reader;
+pub use lexer::{Lexer, Source};
+pub use node::{Node, NodeKind};
 pub use parser::Parser;
+pub use position::Pos;
 pub use token::Token;
 
-/*
 /// Convert the data read from the `reader` and write the output into the `writer`.
-pub fn to_html<R: Read, W: Write>(reader: R, writer: W) {
-}*/
+#[cfg(feature = "std")]
+pub fn to_html<R: Read, W: Write>(reader: R, mut writer: W) -> Result<()> {
+    let mut parser = Parser::new(Lexer::new(reader));
+    let mut generator = Generator::new();
+    loop {
+        match parser.node() {
+            Ok(node) => html::gen(&mut generator, &node, &mut writer)?,
+            Err(Error::Eof) => break,
+            Err(error) => return Err(error),
+        }
+    }
+    html::gen_footnotes(&mut generator, &mut writer)
+}
+
+/// Convert the data read from the `reader` to a DocBook document and write it into the `writer`.
+/// Unlike to_html(), the whole document is parsed before being written, to nest the content of
+/// the sections in their element.
+#[cfg(feature = "std")]
+pub fn to_docbook<R: Read, W: Write>(reader: R, mut writer: W) -> Result<()> {
+    let document = Document::parse(Parser::new(Lexer::new(reader)))?;
+    docbook::gen_document(&mut docbook::Generator::new(), &document.nodes, &document.attributes, &mut writer)
+}
+
+/// Convert the data read from the `reader` to a CommonMark document and write it into the
+/// `writer`.
+#[cfg(feature = "std")]
+pub fn to_markdown<R: Read, W: Write>(reader: R, mut writer: W) -> Result<()> {
+    let document = Document::parse(Parser::new(Lexer::new(reader)))?;
+    markdown::gen_document(&mut markdown::Generator::new(), &document.nodes, &mut writer)
+}
+
+/// Convert the data read from the `reader` to plain text, without any markup, and write it into
+/// the `writer`.
+#[cfg(feature = "std")]
+pub fn to_text<R: Read, W: Write>(reader: R, mut writer: W) -> Result<()> {
+    let document = Document::parse(Parser::new(Lexer::new(reader)))?;
+    text::gen_document(&mut text::Generator::new(), &document.nodes, &mut writer)
+}
@@ -19,25 +19,297 @@
  * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
  */
 
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use position::Pos;
+
+use self::AdmonitionKind::*;
 use self::Tag::*;
 
+/// The kind of an admonition, given by its label.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AdmonitionKind {
+    Caution,
+    Important,
+    Note,
+    Tip,
+    Warning,
+}
+
+impl AdmonitionKind {
+    /// Get the kind of admonition from its label, like `NOTE`.
+    pub fn from_label(label: &[u8]) -> Option<Self> {
+        let kind =
+            match label {
+                b"CAUTION" => Caution,
+                b"IMPORTANT" => Important,
+                b"NOTE" => Note,
+                b"TIP" => Tip,
+                b"WARNING" => Warning,
+                _ => return None,
+            };
+        Some(kind)
+    }
+
+    /// Get the name of the admonition, like `note`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Caution => "caution",
+            Important => "important",
+            Note => "note",
+            Tip => "tip",
+            Warning => "warning",
+        }
+    }
+
+    /// Get the default caption of the admonition, in English, like `Note`.
+    pub fn title(self) -> &'static str {
+        match self {
+            Caution => "Caution",
+            Important => "Important",
+            Note => "Note",
+            Tip => "Tip",
+            Warning => "Warning",
+        }
+    }
+}
+
 /// An attribute like a role or an ID.
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum Attribute {
     Id(String),
+    Named {
+        name: String,
+        value: String,
+    },
+    Option(String),
+    Positional(String),
     Role(String),
 }
 
+/// An author from the author line of the document header, like `Jane Doe <jane@example.com>`.
+#[derive(Debug, PartialEq)]
+pub struct Author {
+    pub email: Option<String>,
+    pub firstname: String,
+    pub lastname: Option<String>,
+    pub middlename: Option<String>,
+    pub name: String,
+}
+
+/// The revision line of the document header, like `v1.0, 2024-01-01: remark`.
+#[derive(Debug, Default, PartialEq)]
+pub struct Revision {
+    pub date: Option<String>,
+    pub number: Option<String>,
+    pub remark: Option<String>,
+}
+
 /// This is a recursive node structure that represents part of a asciidoctor document.
-#[derive(Debug)]
+/// Every node has the position in the source where it starts.
+#[derive(Debug, PartialEq)]
 pub enum Node {
+    Admonition {
+        caption: String,
+        kind: AdmonitionKind,
+        pos: Pos,
+        text: Text,
+    },
+    /// An admonition whose content is a delimited block, like `[NOTE]` followed by `====`.
+    AdmonitionBlock {
+        caption: String,
+        kind: AdmonitionKind,
+        nodes: Vec<Node>,
+        pos: Pos,
+    },
+    /// A description list, with the terms and their definitions, like `term:: definition`.
+    DescriptionList(Vec<(Text, Text)>, Pos),
+    /// An example block, delimited by `====`, which contains other blocks.
+    Example {
+        nodes: Vec<Node>,
+        pos: Pos,
+        title: Option<Text>,
+    },
+    Header {
+        authors: Vec<Author>,
+        pos: Pos,
+        revision: Revision,
+        title: Text,
+    },
+    HorizontalRule(Vec<Attribute>, Pos),
+    /// A block image, like `image::sunset.png[Sunset,300,200]`.
+    Image {
+        alt: String,
+        height: Option<String>,
+        pos: Pos,
+        target: String,
+        title: Option<Text>,
+        width: Option<String>,
+    },
+    /// A listing block, delimited by `----`. Unlike a paragraph with the `[listing]` style, a
+    /// delimited block or a block with the `[source]` style is a `source` block, whose code is
+    /// highlighted.
+    Listing {
+        content: String,
+        language: Option<String>,
+        pos: Pos,
+        source: bool,
+        title: Option<Text>,
+    },
+    /// A literal block, whose content is displayed as is, like a paragraph with the `[literal]`
+    /// style.
+    Literal(String, Pos),
+    OrderedList {
+        attributes: Vec<Attribute>,
+        items: Vec<ListItem>,
+        level: usize,
+        pos: Pos,
+        start: Option<usize>,
+    },
+    /// An open block, delimited by `--`, which contains other blocks.
+    Open(Vec<Node>, Pos),
+    PageBreak(Pos),
+    Paragraph(Text, Vec<Attribute>, Pos),
+    /// A passthrough block, delimited by `++++`, whose content is written as is in the output.
+    Passthrough(String, Pos),
+    /// A quote block, delimited by `____`, with its attribution, like `[quote, Author, Source]`.
+    Quote {
+        author: Option<String>,
+        content: Vec<Node>,
+        pos: Pos,
+        source: Option<String>,
+    },
+    Section {
+        attributes: Vec<Attribute>,
+        level: usize,
+        pos: Pos,
+        title: Text,
+    },
+    /// A sidebar block, delimited by `****`, which contains other blocks.
+    Sidebar {
+        nodes: Vec<Node>,
+        pos: Pos,
+        title: Option<Text>,
+    },
+    /// A listing or literal block whose content has the normal substitutions, like a listing
+    /// block with `[subs="normal"]`.
+    SubstitutedBlock {
+        listing: bool,
+        pos: Pos,
+        text: Text,
+    },
+    Table {
+        attributes: Vec<Attribute>,
+        pos: Pos,
+        rows: Vec<Vec<Text>>,
+    },
+    /// The position of the table of contents, `toc::[]`.
+    Toc(Pos),
+    UnorderedList(Vec<ListItem>, Vec<Attribute>, Pos),
+}
+
+impl Node {
+    /// Get the kind of the node.
+    pub fn kind(&self) -> NodeKind {
+        match *self {
+            Node::Admonition { .. } | Node::AdmonitionBlock { .. } => NodeKind::Admonition,
+            Node::DescriptionList(..) => NodeKind::DescriptionList,
+            Node::Example { .. } => NodeKind::Example,
+            Node::Header { .. } => NodeKind::Header,
+            Node::HorizontalRule(..) => NodeKind::HorizontalRule,
+            Node::Image { .. } => NodeKind::Image,
+            Node::Listing { .. } | Node::SubstitutedBlock { listing: true, .. } => NodeKind::Listing,
+            Node::Literal(..) | Node::SubstitutedBlock { listing: false, .. } => NodeKind::Literal,
+            Node::Open(..) => NodeKind::Open,
+            Node::OrderedList { .. } => NodeKind::OrderedList,
+            Node::PageBreak(_) => NodeKind::PageBreak,
+            Node::Paragraph(..) => NodeKind::Paragraph,
+            Node::Passthrough(..) => NodeKind::Passthrough,
+            Node::Quote { .. } => NodeKind::Quote,
+            Node::Section { .. } => NodeKind::Section,
+            Node::Sidebar { .. } => NodeKind::Sidebar,
+            Node::Table { .. } => NodeKind::Table,
+            Node::Toc(_) => NodeKind::Toc,
+            Node::UnorderedList(..) => NodeKind::UnorderedList,
+        }
+    }
+
+    /// Get the position in the source where the node starts.
+    pub fn pos(&self) -> Pos {
+        match *self {
+            Node::Admonition { pos, .. } | Node::AdmonitionBlock { pos, .. } | Node::Example { pos, .. } |
+                Node::Header { pos, .. } | Node::Image { pos, .. } | Node::Listing { pos, .. } |
+                Node::OrderedList { pos, .. } | Node::Quote { pos, .. } | Node::Section { pos, .. } |
+                Node::Sidebar { pos, .. } | Node::SubstitutedBlock { pos, .. } | Node::Table { pos, .. } => pos,
+            Node::DescriptionList(_, pos) | Node::HorizontalRule(_, pos) | Node::Literal(_, pos) |
+                Node::Open(_, pos) | Node::PageBreak(pos) | Node::Paragraph(_, _, pos) | Node::Passthrough(_, pos) |
+                Node::Toc(pos) | Node::UnorderedList(_, _, pos) => pos,
+        }
+    }
+}
+
+/// The kind of a node, like a paragraph.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum NodeKind {
+    Admonition,
+    DescriptionList,
+    Example,
+    Header,
     HorizontalRule,
+    Image,
+    Listing,
+    Literal,
+    Open,
+    OrderedList,
     PageBreak,
-    Paragraph(Text),
+    Paragraph,
+    Passthrough,
+    Quote,
+    Section,
+    Sidebar,
+    Table,
+    Toc,
+    UnorderedList,
+}
+
+impl NodeKind {
+    /// Get the name of the kind of node, like `paragraph` or `ulist`.
+    pub fn name(self) -> &'static str {
+        match self {
+            NodeKind::Admonition => "admonition",
+            NodeKind::DescriptionList => "dlist",
+            NodeKind::Example => "example",
+            NodeKind::Header => "header",
+            NodeKind::HorizontalRule => "thematic_break",
+            NodeKind::Image => "image",
+            NodeKind::Listing => "listing",
+            NodeKind::Literal => "literal",
+            NodeKind::Open => "open",
+            NodeKind::OrderedList => "olist",
+            NodeKind::PageBreak => "page_break",
+            NodeKind::Paragraph => "paragraph",
+            NodeKind::Passthrough => "pass",
+            NodeKind::Quote => "quote",
+            NodeKind::Section => "section",
+            NodeKind::Sidebar => "sidebar",
+            NodeKind::Table => "table",
+            NodeKind::Toc => "toc",
+            NodeKind::UnorderedList => "ulist",
+        }
+    }
+}
+
+/// An item of a list, like `* item`.
+#[derive(Debug, PartialEq)]
+pub struct ListItem {
+    /// The list nested in this item, like the `.. item` following a `. item`.
+    pub nested: Option<Node>,
+    pub text: Text,
 }
 
 /// A text contains words, links, bold text, …
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct Text {
     pub items: Vec<Item>,
 }
@@ -48,19 +320,104 @@ impl Text {
             items,
         }
     }
+
+    /// Get the text without its formatting.
+    pub fn plain_text(&self) -> String {
+        let mut string = String::new();
+        for item in &self.items {
+            match *item {
+                Item::Button(ref label) => string.push_str(label),
+                Item::CrossReference { ref id, text: None } => string.push_str(id),
+                Item::CrossReference { text: Some(ref text), .. } => string.push_str(&text.plain_text()),
+                // The anchors and the footnotes are not part of the text.
+                Item::Anchor { .. } | Item::Footnote(_) | Item::Icon { .. } => (),
+                Item::InlineImage { ref alt, .. } => string.push_str(alt),
+                Item::Keyboard(ref keys) => string.push_str(&keys.join("+")),
+                Item::LineBreak => string.push('\n'),
+                Item::Literal(ref text) => string.push_str(text),
+                Item::Link { ref target, text: None, .. } => string.push_str(target),
+                Item::Link { text: Some(ref text), .. } => string.push_str(&text.plain_text()),
+                Item::Menu { ref menu, ref path } => {
+                    string.push_str(menu);
+                    for item in path {
+                        string.push_str(" > ");
+                        string.push_str(item);
+                    }
+                },
+                Item::Mark(ref text, _) | Item::Tag(_, ref text, _) => string.push_str(&text.plain_text()),
+                Item::Passthrough(ref content) => string.push_str(content),
+                Item::Quoted { double, ref text } => {
+                    let (open, close) = if double { ('“', '”') } else { ('‘', '’') };
+                    string.push(open);
+                    string.push_str(&text.plain_text());
+                    string.push(close);
+                },
+                Item::Space => string.push(' '),
+                Item::Word(ref word) => string.push_str(word),
+            }
+        }
+        string
+    }
 }
 
 /// A text item, like a word, link, bold text, …
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum Item {
+    /// An inline anchor, like `[[id]]` or `anchor:id[label]`, with the label used as the text of
+    /// the cross references to it.
+    Anchor {
+        id: String,
+        label: Option<String>,
+    },
+    /// A UI button, like `btn:[Save]`.
+    Button(String),
+    CrossReference {
+        id: String,
+        text: Option<Text>,
+    },
+    Footnote(Text),
+    /// A font icon, like `icon:heart[2x]`.
+    Icon {
+        name: String,
+        role: Option<String>,
+        size: Option<String>,
+    },
+    /// An inline image, like `image:icon.png[Icon]`.
+    InlineImage {
+        alt: String,
+        target: String,
+    },
+    Keyboard(Vec<String>),
+    /// Text displayed without any substitution, like the content of `` `+{name}+` ``.
+    Literal(String),
+    /// A forced line break, like the end of a line in a `[%hardbreaks]` paragraph.
+    LineBreak,
+    /// A menu selection, like `menu:File[Save > As]`, with the path to the item in the menu.
+    Menu {
+        menu: String,
+        path: Vec<String>,
+    },
+    /// A link to a URL, like `https://example.com`.
+    Link {
+        /// The attributes of the link macro, like `window=_blank`.
+        attributes: Vec<Attribute>,
+        target: String,
+        text: Option<Text>,
+    },
     Space,
     Mark(Text, Vec<Attribute>),
+    Passthrough(String),
+    /// Text between curved quotes, like ``"`text`"``, or ``'`text`'`` when not `double`.
+    Quoted {
+        double: bool,
+        text: Text,
+    },
     Tag(Tag, Text, Vec<Attribute>),
     Word(String),
 }
 
 /// An asciidoctor tag.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Tag {
     Bold,
     InlineCode,
@@ -71,8 +428,8 @@ pub enum Tag {
 
 impl Tag {
     /// Convert the asciidoctor tag to the HTML tag string.
-    pub fn to_string(&self) -> &str {
-        match *self {
+    pub fn to_string(self) -> &'static str {
+        match self {
             Bold => "strong",
             InlineCode => "code",
             Italic => "em",
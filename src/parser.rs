@@ -21,20 +21,26 @@
 
 //! Parse asciidoctor.
 
-use std::io::BufRead;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 
 use error::{Error, Result};
-use lexer::Lexer;
-use node::{Attribute, Item, Node, Text};
+use lexer::{Lexer, Source};
+use node::{AdmonitionKind, Attribute, Author, Item, ListItem, Node, NodeKind, Revision, Text};
 use node::Attribute::{Id, Role};
 use node::Node::*;
 use node::Tag::*;
+use position::Pos;
 use token::Token;
 use token::Token::*;
 
 macro_rules! parse_text_between {
-    ($func_name:ident, $token:ident, $tag:ident) => {
+    ($func_name:ident, $token:ident, $tag:ident, $is_closed:ident) => {
         fn $func_name(&mut self, attributes: Vec<Attribute>) -> Result<Item> {
+            if !self.$is_closed(&$token)? {
+                return self.literal(attributes);
+            }
             let text = text_between!(self, $token);
             Ok(Item::Tag($tag, text, attributes))
         }
@@ -50,76 +56,1162 @@ macro_rules! text_between {
     }};
 }
 
+/// The deepest section level, `======`, whose title is a h6 heading.
+const MAX_SECTION_LEVEL: usize = 5;
+
 /// Asciidoctor parser.
-pub struct Parser<R: BufRead> {
+pub struct Parser<R: Source> {
+    /// Whether the last parsed text item ends with a word character.
+    after_word: bool,
+    attributes: BTreeMap<String, String>,
+    /// The title of the next block, like `.Title`.
+    block_title: Option<Text>,
+    /// Whether a block was parsed, so that the document header cannot come next.
+    header_done: bool,
+    /// Whether the text being parsed ends at the end of the line, like a title, so that its
+    /// formatting marks cannot be closed on the next lines.
+    single_line: bool,
     tokens: Lexer<R>,
 }
 
-impl<R: BufRead> Parser<R> {
+impl<R: Source> Parser<R> {
     /// Create a new parser from an iterator of tokens.
     /// The resulting nodes can be fetched by calling `Parser::nodes()` which is an iterator over
     /// asciidoctor nodes.
     pub fn new(tokens: Lexer<R>) -> Self {
         Parser {
+            after_word: false,
+            attributes: BTreeMap::new(),
+            block_title: None,
+            header_done: false,
+            single_line: false,
             tokens,
         }
     }
 
-    /// Parse an attribute.
-    fn attribute(&mut self) -> Result<Attribute> {
-        let attribute =
-            match self.tokens.token()? {
-                NumberSign => {
-                    if let Word(word) = self.tokens.token()? {
-                        Id(String::from_utf8(word)?)
-                    } else {
-                        return Err(self.unexpected_token("ident")) // FIXME: does not show the right actual token because it was consumed by the call to token().
-                    }
-                },
-                Word(word) => Role(String::from_utf8(word)?),
-                _ => return Err(self.unexpected_token("ident")), // FIXME: does not show the right actual token because it was consumed by the call to token().
+    /// Parse an admonition block, like `[NOTE]` followed by a `====` delimited block, whose
+    /// content is made of blocks. Without delimiters, the admonition is the next paragraph.
+    fn admonition_block(&mut self, kind: AdmonitionKind) -> Result<Node> {
+        let caption = self.admonition_caption(kind);
+        if let DelimiterLine(b'=', count) = *self.tokens.peek()? {
+            let pos = self.tokens.pos();
+            self.tokens.token()?;
+            let nodes = self.delimited_nodes(&DelimiterLine(b'=', count), NodeKind::Admonition, pos)?;
+            return Ok(AdmonitionBlock {
+                caption,
+                kind,
+                nodes,
+                pos,
+            });
+        }
+        let pos = self.tokens.pos();
+        let text = self.paragraph_text(vec![], false)?;
+        Ok(Admonition {
+            caption,
+            kind,
+            pos,
+            text,
+        })
+    }
+
+    /// Get the caption of an admonition.
+    /// The caption can be localized with an attribute, like `:note-caption: Remarque`.
+    fn admonition_caption(&self, kind: AdmonitionKind) -> String {
+        self.attributes.get(&format!("{}-caption", kind.name()))
+            .cloned()
+            .unwrap_or_else(|| kind.title().to_string())
+    }
+
+    /// Get the kind of admonition if the next tokens are an admonition label, like `NOTE: `.
+    fn admonition_kind(&mut self) -> Result<Option<AdmonitionKind>> {
+        let kind =
+            match self.tokens.peek() {
+                Ok(Word(label)) => AdmonitionKind::from_label(label),
+                _ => None,
             };
-        Ok(attribute)
+        if kind.is_none() || !matches!(self.tokens.peek_nth(1), Ok(&Colon)) ||
+            !matches!(self.tokens.peek_nth(2), Ok(&Space))
+        {
+            return Ok(None);
+        }
+        Ok(kind)
     }
 
-    /// Parse attributes and the node following it.
-    fn attributes(&mut self) -> Result<Vec<Attribute>> {
+    /// Parse an attribute. The role shorthand can specify multiple roles, like `.role1.role2`.
+    fn attribute<I: Iterator<Item=Token>>(&mut self, tokens: &mut I) -> Result<Vec<Attribute>> {
+        let mut list =
+            match tokens.next() {
+                // The id can be followed by roles, like `#intro.lead`.
+                Some(NumberSign) => {
+                    match tokens.next() {
+                        Some(Word(word)) => format!("#{}", String::from_utf8(word)?),
+                        token => return Err(self.unexpected_attribute_token(token, "ident")),
+                    }
+                },
+                Some(Word(word)) => String::from_utf8(word)?,
+                token => return Err(self.unexpected_attribute_token(token, "ident")),
+            };
+        // The values can contain spaces, like `[quote, Author Name]`.
+        for token in tokens.by_ref() {
+            list.push_str(&token.text());
+        }
         let mut attributes = vec![];
-        if *self.tokens.peek()? == OpenSquareBracket {
-            self.eat(OpenSquareBracket)?;
-            attributes.push(self.attribute()?);
-            // TODO: other attributes.
-            self.eat(CloseSquareBracket)?;
+        for (index, value) in split_attribute_list(&list).into_iter().enumerate() {
+            attributes.extend(parse_attribute(value.trim(), index == 0));
         }
         Ok(attributes)
     }
 
+    /// Parse a document attribute entry, like `:name: value`, and the node following it.
+    /// The entries `:!name:` and `:name!:` unset the attribute, while the entry `:name:` sets it
+    /// to an empty value.
+    fn attribute_entry(&mut self) -> Result<Node> {
+        self.eat(Colon)?;
+        // The name is read as is, since it can contain characters which are tokens, like `_`.
+        let line = String::from_utf8(self.tokens.raw_line()?)?;
+        let (name, value) =
+            match line.split_once(':') {
+                Some((name, value)) => (name.to_string(), value),
+                None => return Err(self.unexpected_token(":")),
+            };
+        if let Some(name) = name.strip_prefix('!').or_else(|| name.strip_suffix('!')) {
+            self.attributes.remove(name);
+        }
+        else {
+            self.attributes.insert(name, value.trim().to_string());
+        }
+        self.node()
+    }
+
+    /// Parse an attribute reference, like `{name}` or `{counter:name}`, which is replaced by the
+    /// value of the attribute. A reference to an unknown attribute is kept as is, unless it has a
+    /// default value, like `{name:default}` or `{name:-default}`. The reference `{name:+text}` is
+    /// replaced by the text only when the attribute is set.
+    fn attribute_reference(&mut self, attributes: Vec<Attribute>) -> Result<Item> {
+        let mut index = 1;
+        let mut name = String::new();
+        loop {
+            match self.tokens.peek_nth(index) {
+                Ok(&CloseCurlyBracket) => break,
+                // The default value can contain spaces.
+                Ok(&Space) if name.contains(':') => name.push(' '),
+                Ok(&NewLine) | Ok(&OpenCurlyBracket) | Ok(&Space) | Err(Error::Eof) =>
+                    return self.literal(attributes),
+                Ok(token) => name.push_str(&token.text()),
+                Err(error) => return Err(error),
+            }
+            index += 1;
+        }
+        let value =
+            if let Some(counter) = name.strip_prefix("counter:") {
+                self.increment_counter(counter)
+            }
+            else if let Some((name, text)) = name.split_once(':') {
+                let value = self.attributes.get(name);
+                if let Some(text) = text.strip_prefix('+') {
+                    value.map(|_| text.to_string()).unwrap_or_default()
+                }
+                else {
+                    let default = text.strip_prefix('-').unwrap_or(text);
+                    value.map(String::as_str).unwrap_or(default).to_string()
+                }
+            }
+            else {
+                match self.attributes.get(&name) {
+                    Some(value) => value.clone(),
+                    None => return self.literal(attributes),
+                }
+            };
+        for _ in 0..=index {
+            self.tokens.token()?;
+        }
+        Ok(Item::Word(value))
+    }
+
+    /// Parse an inline anchor, like `[[id]]` or `[[id,label]]`.
+    fn anchor(&mut self) -> Result<Item> {
+        self.eat(OpenSquareBracket)?;
+        self.eat(OpenSquareBracket)?;
+        let mut tokens = vec![];
+        while *self.tokens.peek()? != CloseSquareBracket {
+            tokens.push(self.tokens.token()?);
+        }
+        self.eat(CloseSquareBracket)?;
+        self.eat(CloseSquareBracket)?;
+        let text = tokens_text(&tokens);
+        let (id, label) =
+            match text.split_once(',') {
+                Some((id, label)) => (id.to_string(), Some(label.trim().to_string()).filter(|label| !label.is_empty())),
+                None => (text, None),
+            };
+        Ok(Item::Anchor {
+            id,
+            label,
+        })
+    }
+
+    /// Parse an anchor macro, like `anchor:id[label]`.
+    fn anchor_macro(&mut self) -> Result<Item> {
+        self.eat(Colon)?;
+        let (id, label) = self.macro_target()?;
+        let label = label.trim();
+        Ok(Item::Anchor {
+            id,
+            label: Some(label.to_string()).filter(|label| !label.is_empty()),
+        })
+    }
+
+    /// Parse the comma-separated attributes from the tokens between square brackets, like
+    /// `role, #id`. Empty square brackets have no attributes.
+    fn attributes(&mut self, tokens: Vec<Token>) -> Result<Vec<Attribute>> {
+        if tokens.iter().all(|token| *token == Space) {
+            return Ok(vec![]);
+        }
+        let mut tokens = tokens.into_iter().skip_while(|token| *token == Space);
+        self.attribute(&mut tokens)
+    }
+
+    /// Parse square brackets: they are attributes when they are followed by formatted text,
+    /// otherwise they are literal text.
+    fn attributes_or_brackets(&mut self, attributes: Vec<Attribute>) -> Result<Item> {
+        if self.is_inline_anchor()? {
+            return self.anchor();
+        }
+        self.eat(OpenSquareBracket)?;
+        let mut tokens = vec![];
+        loop {
+            match self.tokens.peek() {
+                Ok(&CloseSquareBracket) => break,
+                Ok(&NewLine) | Err(Error::Eof) => return Ok(Item::Word(format!("[{}", tokens_text(&tokens)))),
+                Ok(_) => tokens.push(self.tokens.token()?),
+                Err(error) => return Err(error),
+            }
+        }
+        self.eat(CloseSquareBracket)?;
+        let is_formatted =
+            match self.tokens.peek() {
+                Ok(token) => is_formatting_mark(token),
+                Err(Error::Eof) => false,
+                Err(error) => return Err(error),
+            };
+        if is_formatted {
+            if !attributes.is_empty() {
+                return Err(self.unexpected_token("formatted text"));
+            }
+            let attributes = self.attributes(tokens)?;
+            self.text_item(attributes)
+        }
+        else {
+            Ok(Item::Word(format!("[{}]", tokens_text(&tokens))))
+        }
+    }
+
+    /// Parse text between backquotes: this is literal monospace text when it is also between
+    /// plus signs, like `` `+{name}+` ``, so that its content is kept as is.
+    fn backquote(&mut self, attributes: Vec<Attribute>) -> Result<Item> {
+        let end =
+            match self.literal_monospace_end()? {
+                Some(end) => end,
+                None => return self.inline_code(attributes),
+            };
+        self.eat(Backquote)?;
+        self.tokens.token()?;
+        let mut tokens = vec![];
+        for _ in 2..end {
+            tokens.push(self.tokens.token()?);
+        }
+        self.tokens.token()?;
+        self.eat(Backquote)?;
+        Ok(Item::Tag(InlineCode, Text::new(vec![Item::Literal(tokens_text(&tokens))]), attributes))
+    }
+
+    /// Parse a block attribute line, like `[#id]`, and the block it applies to.
+    fn block_attributes(&mut self) -> Result<Node> {
+        self.eat(OpenSquareBracket)?;
+        let mut tokens = vec![];
+        while *self.tokens.peek()? != CloseSquareBracket {
+            tokens.push(self.tokens.token()?);
+        }
+        self.eat(CloseSquareBracket)?;
+        self.eat_line_end()?;
+        let attributes = self.attributes(tokens)?;
+        let admonition_kind =
+            match attributes.first() {
+                Some(Role(style)) => AdmonitionKind::from_label(style.as_bytes()),
+                _ => None,
+            };
+        if let Some(kind) = admonition_kind {
+            return self.admonition_block(kind);
+        }
+        let node =
+            match attributes.first() {
+                Some(Role(style)) if style == "listing" || style == "literal" || style == "source" =>
+                    self.verbatim_block(style)?,
+                Some(Role(style)) if style == "example" => self.example_style_block()?,
+                Some(Role(style)) if style == "verse" => self.hardbreaks_paragraph()?,
+                Some(Role(style)) if style == "quote" => {
+                    let attribution: Vec<_> = attributes.iter()
+                        .filter_map(|attribute| match *attribute {
+                            Attribute::Positional(ref value) => Some(value.clone()),
+                            _ => None,
+                        })
+                        .map(|value| Some(value).filter(|value| !value.is_empty()))
+                        .collect();
+                    let author = attribution.first().cloned().flatten();
+                    let source = attribution.get(1).cloned().flatten();
+                    self.quote_with_attribution(author, source)?
+                },
+                _ if attributes.contains(&Attribute::Option("hardbreaks".to_string())) => self.hardbreaks_paragraph()?,
+                _ => self.node()?,
+            };
+        let node =
+            match node {
+                Paragraph(text, _, pos) => Paragraph(text, attributes, pos),
+                Section { level, pos, title, .. } => Section {
+                    attributes,
+                    level,
+                    pos,
+                    title,
+                },
+                Listing { content, pos, .. } if named_attribute(&attributes, "subs") == Some("normal") =>
+                    SubstitutedBlock {
+                        listing: true,
+                        pos,
+                        text: self.substituted_text(&content)?,
+                    },
+                Literal(content, pos) if named_attribute(&attributes, "subs") == Some("normal") =>
+                    SubstitutedBlock {
+                        listing: false,
+                        pos,
+                        text: self.substituted_text(&content)?,
+                    },
+                Listing { content, pos, source, title, .. } => Listing {
+                    content: indent_listing(content, &attributes),
+                    language: source_language(&attributes),
+                    pos,
+                    source,
+                    title,
+                },
+                Table { pos, rows, .. } => Table {
+                    attributes,
+                    pos,
+                    rows,
+                },
+                OrderedList { items, level, pos, start, .. } => OrderedList {
+                    start: named_attribute(&attributes, "start").and_then(|start| start.parse().ok()).or(start),
+                    attributes,
+                    items,
+                    level,
+                    pos,
+                },
+                HorizontalRule(_, pos) => HorizontalRule(attributes, pos),
+                UnorderedList(items, _, pos) => UnorderedList(items, attributes, pos),
+                node => node,
+            };
+        Ok(node)
+    }
+
+    /// Parse a block image, like `image::sunset.png[Sunset,300,200]`, whose positional
+    /// attributes are the alternative text, the width and the height.
+    fn block_image(&mut self) -> Result<Node> {
+        let pos = self.tokens.pos();
+        self.tokens.token()?;
+        self.eat(DoubleColon)?;
+        let (target, alt, width, height) = self.image_macro()?;
+        Ok(Image {
+            alt,
+            height,
+            pos,
+            target,
+            title: self.block_title.take(),
+            width,
+        })
+    }
+
+    /// Parse a block title, like `.Title`, and the block it applies to.
+    fn block_title(&mut self) -> Result<Node> {
+        self.eat(DotRun(1))?;
+        self.block_title = Some(self.line_text()?);
+        let node = self.node();
+        self.block_title = None;
+        node
+    }
+
+    /// Parse a UI button macro, like `btn:[Save]`.
+    fn button(&mut self) -> Result<Item> {
+        self.eat(Colon)?;
+        if !self.is_closed(&CloseSquareBracket)? {
+            return Ok(Item::Word("btn:".to_string()));
+        }
+        self.eat(OpenSquareBracket)?;
+        let mut tokens = vec![];
+        while *self.tokens.peek()? != CloseSquareBracket {
+            tokens.push(self.tokens.token()?);
+        }
+        self.eat(CloseSquareBracket)?;
+        Ok(Item::Button(tokens_text(&tokens).trim().to_string()))
+    }
+
+    /// Parse a cross reference, like `<<id>>` or `<<id,custom text>>`.
+    fn cross_reference(&mut self, attributes: Vec<Attribute>) -> Result<Item> {
+        if !self.is_closed(&DoubleGt)? {
+            return self.literal(attributes);
+        }
+        self.eat(DoubleLt)?;
+        let mut id = String::new();
+        let text_start =
+            loop {
+                match self.tokens.token()? {
+                    DoubleGt => return Ok(Item::CrossReference {
+                        id,
+                        text: None,
+                    }),
+                    Word(word) => {
+                        let word = String::from_utf8(word)?;
+                        match word.split_once(',') {
+                            Some((id_end, text_start)) => {
+                                id.push_str(id_end);
+                                break text_start.to_string();
+                            },
+                            None => id.push_str(&word),
+                        }
+                    },
+                    token => id.push_str(&token.text()),
+                }
+            };
+        let mut items = vec![];
+        if !text_start.is_empty() {
+            items.push(Item::Word(text_start));
+        }
+        items.append(&mut self.text_while(|token| token != &DoubleGt)?.items);
+        self.eat(DoubleGt)?;
+        while let Some(&Item::Space) = items.first() {
+            items.remove(0);
+        }
+        Ok(Item::CrossReference {
+            id,
+            text: Some(Text::new(items)),
+        })
+    }
+
+    /// Parse a description list, with one term on each line, like `term:: definition`.
+    /// The definition can also be on the next lines, when they are indented.
+    fn description_list(&mut self) -> Result<Node> {
+        let pos = self.tokens.pos();
+        let mut items = vec![];
+        while self.is_description_list_item()? {
+            let term = self.text_while(|token| token != &DoubleColon)?;
+            self.eat(DoubleColon)?;
+            self.spaces()?;
+            let mut definition = vec![];
+            loop {
+                let mut line = self.text_while(|token| token != &NewLine)?;
+                definition.append(&mut line.items);
+                match self.tokens.token() {
+                    Ok(_) => (),
+                    Err(Error::Eof) => break,
+                    Err(error) => return Err(error),
+                }
+                if !matches!(self.tokens.peek(), Ok(&Space)) {
+                    break;
+                }
+                self.spaces()?;
+                if !definition.is_empty() {
+                    definition.push(Item::Space);
+                }
+            }
+            items.push((term, Text::new(definition)));
+        }
+        Ok(DescriptionList(items, pos))
+    }
+
+    /// Read the lines as is until the closing `delimiter` line of a block of the specified `kind`,
+    /// whose opening delimiter is at `pos`.
+    fn delimited_lines(&mut self, delimiter: &[u8], kind: NodeKind, pos: Pos) -> Result<Vec<String>> {
+        let mut lines = vec![];
+        loop {
+            let line =
+                match self.tokens.raw_line() {
+                    Ok(line) => line,
+                    Err(Error::Eof) => return Err(Error::UnterminatedBlock { kind, pos }),
+                    Err(error) => return Err(error),
+                };
+            if line == delimiter {
+                break;
+            }
+            lines.push(String::from_utf8(line)?);
+        }
+        Ok(lines)
+    }
+
+    /// Parse the blocks of a delimited block of the specified `kind`, like an example block, until
+    /// its closing `delimiter` line. The opening delimiter is at `pos`.
+    fn delimited_nodes(&mut self, delimiter: &Token, kind: NodeKind, pos: Pos) -> Result<Vec<Node>> {
+        // A block title that the block does not keep is not the title of its first block.
+        self.block_title = None;
+        let mut nodes = vec![];
+        loop {
+            match self.tokens.peek() {
+                Ok(token) if token == delimiter => {
+                    if self.is_nested_delimiter(delimiter)? {
+                        nodes.push(self.node()?);
+                    }
+                    else {
+                        self.tokens.token()?;
+                        break;
+                    }
+                },
+                Ok(&NewLine) => {
+                    self.tokens.token()?;
+                },
+                Ok(_) => nodes.push(self.node()?),
+                Err(Error::Eof) => return Err(Error::UnterminatedBlock { kind, pos }),
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(nodes)
+    }
+
+    /// Get the document attributes defined so far, like `:name: value`.
+    pub fn document_attributes(&self) -> &BTreeMap<String, String> {
+        &self.attributes
+    }
+
     /// Eat the expected token or return an error if a different token is found.
     fn eat(&mut self, expected: Token) -> Result<()> {
-        let token = self.tokens.token()?;
-        if token != expected {
-            return Err(self.unexpected_token(&expected.to_string())); // FIXME: does not show the right actual token because it was consumed by the call to token().
+        if *self.tokens.peek()? != expected {
+            return Err(self.unexpected_token(&expected.to_string()));
         }
+        self.tokens.token()?;
         Ok(())
     }
 
+    /// Eat the newline ending a line, unless the line is the last of the document.
+    fn eat_line_end(&mut self) -> Result<()> {
+        match self.tokens.peek() {
+            Ok(_) => self.eat(NewLine),
+            Err(Error::Eof) => Ok(()),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Parse the footnote macro, like `footnote:[text]`, after the `footnote` word.
+    fn footnote(&mut self) -> Result<Item> {
+        self.eat(Colon)?;
+        if !self.is_closed(&CloseSquareBracket)? {
+            return Ok(Item::Word("footnote:".to_string()));
+        }
+        self.eat(OpenSquareBracket)?;
+        let text = self.text_while(|token| token != &CloseSquareBracket)?;
+        self.eat(CloseSquareBracket)?;
+        Ok(Item::Footnote(text))
+    }
+
+    /// Parse a keyboard macro, like `kbd:[Ctrl+Alt+Del]`.
+    fn keyboard(&mut self) -> Result<Item> {
+        self.eat(Colon)?;
+        if !self.is_closed(&CloseSquareBracket)? {
+            return Ok(Item::Word("kbd:".to_string()));
+        }
+        self.eat(OpenSquareBracket)?;
+        let mut tokens = vec![];
+        while *self.tokens.peek()? != CloseSquareBracket {
+            tokens.push(self.tokens.token()?);
+        }
+        self.eat(CloseSquareBracket)?;
+        Ok(Item::Keyboard(parse_keys(&tokens_text(&tokens))))
+    }
+
+    /// Parse a paragraph whose lines are separated by line breaks, like a `[verse]` block.
+    fn hardbreaks_paragraph(&mut self) -> Result<Node> {
+        let pos = self.tokens.pos();
+        let text = self.paragraph_text(vec![], true)?;
+        Ok(Paragraph(text, vec![], pos))
+    }
+
+    /// Parse the document header: the title, like `= Title`, followed by the optional author
+    /// and revision lines.
+    fn header(&mut self) -> Result<Node> {
+        let pos = self.tokens.pos();
+        self.tokens.token()?;
+        self.eat(Space)?;
+        let title = self.line_text()?;
+        match self.tokens.token() {
+            Ok(NewLine) | Err(Error::Eof) => (),
+            Ok(_) => return Err(self.unexpected_token("newline")),
+            Err(error) => return Err(error),
+        }
+        let mut authors = vec![];
+        if let Some(line) = self.header_line()? {
+            authors = parse_authors(&line);
+            for (index, author) in authors.iter().enumerate() {
+                // The attributes of the other authors have a suffix, like `author_2`.
+                let suffix = if index == 0 { String::new() } else { format!("_{}", index + 1) };
+                let values = [
+                    ("author", Some(&author.name)),
+                    ("email", author.email.as_ref()),
+                    ("firstname", Some(&author.firstname)),
+                    ("lastname", author.lastname.as_ref()),
+                    ("middlename", author.middlename.as_ref()),
+                ];
+                for &(name, value) in &values {
+                    if let Some(value) = value {
+                        self.attributes.insert(format!("{}{}", name, suffix), value.clone());
+                    }
+                }
+            }
+        }
+        let mut revision = Revision::default();
+        if let Some(line) = self.header_line()? {
+            revision = parse_revision(&line);
+            let values = [
+                ("revdate", &revision.date),
+                ("revnumber", &revision.number),
+                ("revremark", &revision.remark),
+            ];
+            for &(name, value) in &values {
+                if let Some(ref value) = *value {
+                    self.attributes.insert(name.to_string(), value.clone());
+                }
+            }
+        }
+        Ok(Header {
+            authors,
+            pos,
+            revision,
+            title,
+        })
+    }
+
+    /// Read the next line of the document header, if the header continues.
+    fn header_line(&mut self) -> Result<Option<String>> {
+        match self.tokens.peek_char() {
+            // An empty line ends the header and an attribute entry is not an author or
+            // revision line.
+            Ok(b'\n') | Ok(b'\r') | Ok(b':') | Err(Error::Eof) => Ok(None),
+            Ok(_) => Ok(Some(String::from_utf8(self.tokens.raw_line()?)?)),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Parse an example block delimited by `====`, which contains other blocks.
+    fn example_block(&mut self) -> Result<Node> {
+        let title = self.block_title.take();
+        let pos = self.tokens.pos();
+        let delimiter = self.tokens.token()?;
+        Ok(Example {
+            nodes: self.delimited_nodes(&delimiter, NodeKind::Example, pos)?,
+            pos,
+            title,
+        })
+    }
+
+    /// Parse a block with the `[example]` style, which is either an example block delimited by
+    /// `====` or the next paragraph.
+    fn example_style_block(&mut self) -> Result<Node> {
+        if let DelimiterLine(b'=', _) = *self.tokens.peek()? {
+            return self.example_block();
+        }
+        let title = self.block_title.take();
+        let pos = self.tokens.pos();
+        Ok(Example {
+            nodes: vec![self.paragraph_starting_with(vec![], pos)?],
+            pos,
+            title,
+        })
+    }
+
     /// Parse an horizontal rule.
     fn horizontal_rule(&mut self) -> Result<Node> {
+        let pos = self.tokens.pos();
         self.eat(TripleApos)?;
-        Ok(HorizontalRule)
+        Ok(HorizontalRule(vec![], pos))
+    }
+
+    parse_text_between!(bold, Star, Bold, is_constrained_closed);
+    parse_text_between!(inline_code, Backquote, InlineCode, is_constrained_closed);
+    parse_text_between!(italic, Underscore, Italic, is_constrained_closed);
+    parse_text_between!(subscript, Tilde, SubScript, is_closed);
+    parse_text_between!(superscript, Caret, SuperScript, is_closed);
+    parse_text_between!(unconstrained_bold, DoubleStar, Bold, is_closed);
+    parse_text_between!(unconstrained_inline_code, DoubleBackquote, InlineCode, is_closed);
+    parse_text_between!(unconstrained_italic, DoubleUnderscore, Italic, is_closed);
+
+    /// Get the offset added to the levels of the sections, from the `leveloffset` attribute.
+    fn level_offset(&self) -> isize {
+        self.attributes.get("leveloffset")
+            .and_then(|offset| offset.parse().ok())
+            .unwrap_or(0)
+    }
+
+    /// Parse the text until the end of the line, like a title, whose formatting marks must be
+    /// closed on this line.
+    fn line_text(&mut self) -> Result<Text> {
+        self.single_line = true;
+        let text = self.text_while(|token| token != &NewLine);
+        self.single_line = false;
+        text
+    }
+
+    /// Parse a listing block delimited by `----`, whose content is kept as is.
+    fn listing_block(&mut self) -> Result<Node> {
+        let pos = self.tokens.pos();
+        let delimiter = self.tokens.token()?.to_string();
+        self.eat_line_end()?;
+        let mut lines = self.delimited_lines(delimiter.as_bytes(), NodeKind::Listing, pos)?;
+        strip_indentation(&mut lines, 0);
+        Ok(Listing {
+            content: lines.join("\n"),
+            language: None,
+            pos,
+            source: true,
+            title: self.block_title.take(),
+        })
+    }
+
+    /// Parse a literal block delimited by `....`, whose lines are kept as is, including their
+    /// indentation.
+    fn literal_block(&mut self) -> Result<Node> {
+        let pos = self.tokens.pos();
+        let delimiter = self.tokens.token()?.to_string();
+        self.eat_line_end()?;
+        let lines = self.delimited_lines(delimiter.as_bytes(), NodeKind::Literal, pos)?;
+        Ok(Literal(lines.join("\n"), pos))
+    }
+
+    /// Get the index of the closing `+` of literal monospace text, like `` `+{name}+` ``, if the
+    /// next tokens are such a text.
+    fn literal_monospace_end(&mut self) -> Result<Option<usize>> {
+        let is_plus = |token: &Token| *token == Plus;
+        if !matches!(self.tokens.peek_nth(1), Ok(token) if is_plus(token)) {
+            return Ok(None);
+        }
+        let mut index = 2;
+        loop {
+            match self.tokens.peek_nth(index) {
+                Ok(token) if is_plus(token) => {
+                    if index > 2 && matches!(self.tokens.peek_nth(index + 1), Ok(&Backquote)) {
+                        return Ok(Some(index));
+                    }
+                },
+                Ok(&NewLine) => {
+                    // An empty line ends the paragraph.
+                    if matches!(self.tokens.peek_nth(index + 1), Ok(&NewLine)) {
+                        return Ok(None);
+                    }
+                },
+                Ok(_) => (),
+                Err(Error::Eof) => return Ok(None),
+                Err(error) => return Err(error),
+            }
+            index += 1;
+        }
+    }
+
+    /// Parse the text of a list item, which continues on the next lines until the next item.
+    fn list_item_text(&mut self) -> Result<Text> {
+        let mut text = vec![];
+        loop {
+            let mut line = self.text_while(|token| token != &NewLine)?;
+            text.append(&mut line.items);
+            match self.tokens.token() {
+                Ok(_) => (),
+                Err(Error::Eof) => break,
+                Err(error) => return Err(error),
+            }
+            let is_list_item = self.unordered_list_marker()?.is_some() || self.ordered_list_marker()?.is_some();
+            if is_list_item || self.is_paragraph_end()? {
+                break;
+            }
+            if text.last() != Some(&Item::LineBreak) {
+                text.push(Item::Space);
+            }
+        }
+        Ok(Text::new(text))
+    }
+
+    /// Parse a token as literal text.
+    fn literal(&mut self, _attributes: Vec<Attribute>) -> Result<Item> {
+        let token = self.tokens.token()?;
+        Ok(Item::Word(token.text()))
+    }
+
+    /// Parse an icon, like `icon:heart[2x,role=red]`, after its `icon` name. The first
+    /// positional attribute is the size of the icon.
+    /// Without the font icons, enabled by `:icons: font`, the icon is its name between brackets.
+    fn icon(&mut self) -> Result<Item> {
+        self.eat(Colon)?;
+        let (name, list) = self.macro_target()?;
+        if self.attributes.get("icons").map(String::as_str) != Some("font") {
+            return Ok(Item::Word(format!("[{}]", name)));
+        }
+        let mut role = None;
+        let mut size = None;
+        for (index, value) in split_attribute_list(&list).into_iter().enumerate() {
+            let value = value.trim();
+            match value.split_once('=') {
+                Some(("role", role_value)) => role = Some(role_value.trim_matches('"').to_string()),
+                Some(("size", size_value)) => size = Some(size_value.trim_matches('"').to_string()),
+                None if index == 0 && !value.is_empty() => size = Some(value.to_string()),
+                _ => (),
+            }
+        }
+        Ok(Item::Icon {
+            name,
+            role,
+            size,
+        })
+    }
+
+    /// Parse the target and the attributes of an image macro, like `sunset.png[Sunset,300,200]`,
+    /// which are the alternative text, the width and the height.
+    /// The default alternative text is the name of the file without its extension.
+    fn image_macro(&mut self) -> Result<(String, String, Option<String>, Option<String>)> {
+        let (target, list) = self.macro_target()?;
+        let mut values = [None, None, None];
+        for (index, value) in split_attribute_list(&list).into_iter().enumerate() {
+            let (index, value) =
+                match value.split_once('=') {
+                    Some(("alt", value)) => (0, value),
+                    Some(("width", value)) => (1, value),
+                    Some(("height", value)) => (2, value),
+                    _ => (index, value),
+                };
+            let value = value.trim().trim_matches('"');
+            if index < values.len() && !value.is_empty() {
+                values[index] = Some(value.to_string());
+            }
+        }
+        let [alt, width, height] = values;
+        let alt = alt.unwrap_or_else(|| image_stem(&target).to_string());
+        Ok((target, alt, width, height))
     }
 
-    parse_text_between!(bold, Star, Bold);
-    parse_text_between!(inline_code, Backquote, InlineCode);
-    parse_text_between!(italic, Underscore, Italic);
-    parse_text_between!(subscript, Tilde, SubScript);
-    parse_text_between!(superscript, Caret, SuperScript);
-    parse_text_between!(unconstrained_bold, DoubleStar, Bold);
-    parse_text_between!(unconstrained_inline_code, DoubleBackquote, InlineCode);
-    parse_text_between!(unconstrained_italic, DoubleUnderscore, Italic);
+    /// Increment the counter attribute `name` and return its new value.
+    /// The counter starts at 1 when the attribute is not set to a number.
+    fn increment_counter(&mut self, name: &str) -> String {
+        let value =
+            match self.attributes.get(name).and_then(|value| value.parse::<i64>().ok()) {
+                Some(number) => (number + 1).to_string(),
+                None => "1".to_string(),
+            };
+        self.attributes.insert(name.to_string(), value.clone());
+        value
+    }
+
+    /// Parse the node following the leading spaces of a line: the spaces are stripped, but an
+    /// indented line is never an attribute entry, a line of block attributes or a break.
+    fn indented_node(&mut self) -> Result<Node> {
+        match *self.tokens.peek()? {
+            Space => {
+                self.tokens.token()?;
+                self.indented_node()
+            },
+            Colon | OpenSquareBracket | TripleApos | TripleLt => self.paragraph(),
+            _ => self.node(),
+        }
+    }
+
+    /// Parse an inline image, like `image:icon.png[Icon]`, after its `image` name.
+    fn inline_image(&mut self) -> Result<Item> {
+        self.eat(Colon)?;
+        let (target, alt, _, _) = self.image_macro()?;
+        Ok(Item::InlineImage {
+            alt,
+            target,
+        })
+    }
+
+    /// Check if the colon that is the next token starts an attribute entry, like `:name: value`.
+    fn is_attribute_entry(&mut self) -> Result<bool> {
+        let mut name = String::new();
+        let mut index = 1;
+        loop {
+            match self.tokens.peek_nth(index) {
+                Ok(&Colon) => break,
+                Ok(token) if matches!(*token, DoubleUnderscore | Underscore | Word(_)) => name.push_str(&token.text()),
+                Ok(_) | Err(Error::Eof) => return Ok(false),
+                Err(error) => return Err(error),
+            }
+            index += 1;
+        }
+        Ok(is_attribute_name(&name))
+    }
+
+    /// Check if the square bracket that is the next token starts a line of block attributes,
+    /// like `[#id]` alone on its line.
+    fn is_block_attribute_line(&mut self) -> Result<bool> {
+        let mut index = 1;
+        loop {
+            match self.tokens.peek_nth(index) {
+                Ok(&CloseSquareBracket) => break,
+                Ok(&NewLine) | Err(Error::Eof) => return Ok(false),
+                Ok(_) => index += 1,
+                Err(error) => return Err(error),
+            }
+        }
+        match self.tokens.peek_nth(index + 1) {
+            Ok(&NewLine) | Err(Error::Eof) => Ok(true),
+            Ok(_) => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Get the index of the token closing the formatting `mark` that is the next token, if it
+    /// is closed before the end of the paragraph.
+    /// A mark closed right away, like `****`, has no content, so it is not formatted text.
+    fn closing_index(&mut self, mark: &Token) -> Result<Option<usize>> {
+        let mut index = 1;
+        loop {
+            let is_newline =
+                match self.tokens.peek_nth(index) {
+                    Ok(token) if token == mark && index == 1 => return Ok(None),
+                    Ok(token) if token == mark => return Ok(Some(index)),
+                    Ok(token) => *token == NewLine,
+                    Err(Error::Eof) => return Ok(None),
+                    Err(error) => return Err(error),
+                };
+            if is_newline {
+                if self.single_line {
+                    return Ok(None);
+                }
+                // An empty line ends the paragraph.
+                match self.tokens.peek_nth(index + 1) {
+                    Ok(&NewLine) | Err(Error::Eof) => return Ok(None),
+                    Ok(_) => (),
+                    Err(error) => return Err(error),
+                }
+            }
+            index += 1;
+        }
+    }
+
+    /// Check if the next line is a description list item, like `term:: definition`.
+    fn is_description_list_item(&mut self) -> Result<bool> {
+        let mut index = 0;
+        loop {
+            match self.tokens.peek_nth(index) {
+                Ok(&DoubleColon) => break,
+                Ok(&NewLine) | Err(Error::Eof) => return Ok(false),
+                Ok(_) => index += 1,
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(index > 0 && matches!(self.tokens.peek_nth(index + 1), Ok(&NewLine) | Ok(&Space) | Err(Error::Eof)))
+    }
+
+    /// Check if the `delimiter` line that is the next token opens a block nested in a block
+    /// with the same delimiter, instead of closing it: it is directly followed by content and
+    /// both blocks are closed by the next delimiter lines.
+    /// A delimiter line directly followed by content opens a block and the others close one.
+    fn is_nested_delimiter(&mut self, delimiter: &Token) -> Result<bool> {
+        if !self.is_opening_delimiter(0) {
+            return Ok(false);
+        }
+        let mut depth = 2;
+        let mut index = 1;
+        loop {
+            match self.tokens.peek_nth(index) {
+                Ok(token) if token == delimiter => {
+                    if self.is_opening_delimiter(index) {
+                        depth += 1;
+                    }
+                    else {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Ok(true);
+                        }
+                    }
+                },
+                Ok(_) => (),
+                Err(Error::Eof) => return Ok(false),
+                Err(error) => return Err(error),
+            }
+            index += 1;
+        }
+    }
+
+    /// Check if the delimiter line at the `index` of the next tokens is directly followed by
+    /// content, so that it opens a block.
+    fn is_opening_delimiter(&mut self, index: usize) -> bool {
+        matches!(self.tokens.peek_nth(index + 1), Ok(&NewLine)) &&
+            !matches!(self.tokens.peek_nth(index + 2), Ok(&NewLine) | Err(Error::Eof))
+    }
+
+    /// Check if the next token is `token`, which is false at the end of the document.
+    fn is_next(&mut self, token: &Token) -> Result<bool> {
+        match self.tokens.peek() {
+            Ok(next) => Ok(next == token),
+            Err(Error::Eof) => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Check if the next line ends the paragraph: an empty line or a line starting a block, like
+    /// a listing block delimiter or a block attribute line.
+    fn is_paragraph_end(&mut self) -> Result<bool> {
+        match self.tokens.peek() {
+            Ok(&NewLine) | Ok(&DelimiterLine(..)) | Err(Error::Eof) => Ok(true),
+            Ok(&OpenSquareBracket) => self.is_block_attribute_line(),
+            Ok(&Pipe) => self.is_table_delimiter(),
+            Ok(_) => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Check if the next tokens are an inline anchor, like `[[id]]`: unlike a list of attributes,
+    /// it is between double square brackets and its id does not contain spaces.
+    fn is_inline_anchor(&mut self) -> Result<bool> {
+        if !matches!(self.tokens.peek_nth(1), Ok(&OpenSquareBracket)) {
+            return Ok(false);
+        }
+        let mut index = 2;
+        let mut in_label = false;
+        loop {
+            match self.tokens.peek_nth(index) {
+                Ok(&CloseSquareBracket) => break,
+                Ok(&Space) if !in_label => return Ok(false),
+                Ok(&NewLine) | Ok(&OpenSquareBracket) | Err(Error::Eof) => return Ok(false),
+                Ok(Word(word)) if word.starts_with(b",") && index == 2 => return Ok(false),
+                Ok(Word(word)) if word.contains(&b',') => in_label = true,
+                Ok(_) => (),
+                Err(error) => return Err(error),
+            }
+            index += 1;
+        }
+        Ok(index > 2 && matches!(self.tokens.peek_nth(index + 1), Ok(&CloseSquareBracket)))
+    }
+
+    /// Check if the next tokens are a hard line break, a space and a `+` ending the line.
+    fn is_line_break(&mut self) -> Result<bool> {
+        Ok(matches!(self.tokens.peek(), Ok(&Space)) && matches!(self.tokens.peek_nth(1), Ok(&Plus)) &&
+           matches!(self.tokens.peek_nth(2), Ok(&NewLine) | Err(Error::Eof)))
+    }
+
+    /// Check if the next line is a single empty line followed by a line of text.
+    fn is_single_empty_line(&mut self) -> Result<bool> {
+        Ok(matches!(self.tokens.peek(), Ok(&NewLine)) &&
+           !matches!(self.tokens.peek_nth(1), Ok(&NewLine) | Ok(&DelimiterLine(..)) | Ok(&OpenSquareBracket) | Err(Error::Eof)))
+    }
+
+    /// Check if the pipe that is the next token starts a table delimiter line, `|===`.
+    fn is_table_delimiter(&mut self) -> Result<bool> {
+        let is_equals = matches!(self.tokens.peek_nth(1), Ok(Word(word)) if word == b"===");
+        Ok(is_equals && matches!(self.tokens.peek_nth(2), Ok(&NewLine) | Err(Error::Eof)))
+    }
+
+    /// Check if the next line is a block title, like `.Title`: unlike an ordered list item, the
+    /// dot is not followed by a space.
+    fn is_block_title(&mut self) -> Result<bool> {
+        Ok(matches!(self.tokens.peek(), Ok(&DotRun(1))) &&
+           !matches!(self.tokens.peek_nth(1), Ok(&Space) | Ok(&NewLine) | Ok(&DotRun(_)) | Err(Error::Eof)))
+    }
+
+    /// Check if the next tokens are a block image line, like `image::sunset.png[Sunset]`.
+    fn is_block_image(&mut self) -> Result<bool> {
+        let is_image = matches!(self.tokens.peek(), Ok(Word(word)) if word == b"image");
+        if !is_image || !matches!(self.tokens.peek_nth(1), Ok(&DoubleColon)) {
+            return Ok(false);
+        }
+        match self.macro_end(2)? {
+            Some(index) => Ok(matches!(self.tokens.peek_nth(index + 1), Ok(&NewLine) | Err(Error::Eof))),
+            None => Ok(false),
+        }
+    }
+
+    /// Check if the next tokens are the table of contents macro line, `toc::[]`.
+    fn is_toc_macro(&mut self) -> Result<bool> {
+        let is_toc = matches!(self.tokens.peek(), Ok(Word(word)) if word == b"toc");
+        Ok(is_toc &&
+           matches!(self.tokens.peek_nth(1), Ok(&DoubleColon)) &&
+           matches!(self.tokens.peek_nth(2), Ok(&OpenSquareBracket)) &&
+           matches!(self.tokens.peek_nth(3), Ok(&CloseSquareBracket)) &&
+           matches!(self.tokens.peek_nth(4), Ok(&NewLine) | Err(Error::Eof)))
+    }
+
+    /// Check if the formatting `mark` that is the next token is closed before the end of the
+    /// paragraph.
+    fn is_closed(&mut self, mark: &Token) -> Result<bool> {
+        Ok(self.closing_index(mark)?.is_some())
+    }
+
+    /// Check if the constrained formatting `mark` that is the next token is closed and at word
+    /// boundaries: a constrained mark cannot start or end in the middle of a word.
+    fn is_constrained_closed(&mut self, mark: &Token) -> Result<bool> {
+        if self.after_word {
+            return Ok(false);
+        }
+        let index =
+            match self.closing_index(mark)? {
+                Some(index) => index,
+                None => return Ok(false),
+            };
+        let before_word =
+            match self.tokens.peek_nth(index + 1) {
+                Ok(Word(word)) => word.first().is_some_and(u8::is_ascii_alphanumeric),
+                _ => false,
+            };
+        Ok(!before_word)
+    }
+
+    /// Get the index of the closing square bracket of a macro whose target starts at the token
+    /// at `index`, like `sunset.png[Sunset]`, if the next tokens are such a macro.
+    fn macro_end(&mut self, mut index: usize) -> Result<Option<usize>> {
+        let start = index;
+        loop {
+            match self.tokens.peek_nth(index) {
+                Ok(&OpenSquareBracket) if index > start => break,
+                Ok(&OpenSquareBracket) | Ok(&Space) | Ok(&NewLine) | Err(Error::Eof) => return Ok(None),
+                Ok(_) => index += 1,
+                Err(error) => return Err(error),
+            }
+        }
+        loop {
+            index += 1;
+            match self.tokens.peek_nth(index) {
+                Ok(&CloseSquareBracket) => return Ok(Some(index)),
+                Ok(&NewLine) | Err(Error::Eof) => return Ok(None),
+                Ok(_) => (),
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Parse the target and the attribute list of a macro, like `sunset.png[Sunset,300,200]`.
+    fn macro_target(&mut self) -> Result<(String, String)> {
+        let mut tokens = vec![];
+        while *self.tokens.peek()? != OpenSquareBracket {
+            tokens.push(self.tokens.token()?);
+        }
+        let target = tokens_text(&tokens);
+        self.eat(OpenSquareBracket)?;
+        let mut tokens = vec![];
+        while *self.tokens.peek()? != CloseSquareBracket {
+            tokens.push(self.tokens.token()?);
+        }
+        self.eat(CloseSquareBracket)?;
+        Ok((target, tokens_text(&tokens)))
+    }
+
+    /// Parse a menu macro, like `menu:File[Save > As]`, whose path is separated by `>`.
+    fn menu(&mut self) -> Result<Item> {
+        self.eat(Colon)?;
+        let (menu, path) = self.macro_target()?;
+        let path = path.split('>')
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect();
+        Ok(Item::Menu {
+            menu,
+            path,
+        })
+    }
 
     /// Parse a mark.
     fn mark(&mut self, attributes: Vec<Attribute>) -> Result<Item> {
+        if !self.is_closed(&NumberSign)? {
+            return self.literal(attributes);
+        }
         let text = text_between!(self, NumberSign);
         Ok(Item::Mark(text, attributes))
     }
@@ -130,65 +1222,423 @@ impl<R: BufRead> Parser<R> {
             match *self.tokens.peek()? {
                 TripleApos => Self::horizontal_rule,
                 TripleLt => Self::page_break,
-                NewLine | Space => {
+                DelimiterLine(b'-', 2) => Self::open_block,
+                DelimiterLine(b'-', _) => Self::listing_block,
+                DelimiterLine(b'.', _) => Self::literal_block,
+                DelimiterLine(b'=', _) => Self::example_block,
+                DelimiterLine(b'*', _) => Self::sidebar_block,
+                DelimiterLine(b'_', _) => Self::quote_block,
+                DelimiterLine(b'+', _) => Self::passthrough_block,
+                EqualsRun(1) if !self.header_done => {
+                    // An included document whose level is shifted starts with a section.
+                    if self.level_offset() == 0 && matches!(self.tokens.peek_nth(1), Ok(&Space)) {
+                        Self::header
+                    }
+                    else {
+                        Self::section
+                    }
+                },
+                EqualsRun(_) => Self::section,
+                NewLine => {
                     self.tokens.token()?;
                     Self::node
                 },
-                Backquote | Caret | CloseSquareBracket | DoubleBackquote | DoubleStar |
-                    DoubleUnderscore | NumberSign | OpenSquareBracket | Star | Tilde |
-                    Underscore | Word(_) =>
+                Space => {
+                    self.tokens.token()?;
+                    Self::indented_node
+                },
+                Colon => {
+                    if self.is_attribute_entry()? {
+                        Self::attribute_entry
+                    }
+                    else {
+                        Self::paragraph
+                    }
+                },
+                Pipe => {
+                    if self.is_table_delimiter()? {
+                        Self::table
+                    }
+                    else {
+                        Self::paragraph
+                    }
+                },
+                OpenSquareBracket => {
+                    if self.is_block_attribute_line()? {
+                        Self::block_attributes
+                    }
+                    else {
+                        Self::paragraph
+                    }
+                },
+                DotRun(_) => {
+                    if self.is_block_title()? {
+                        Self::block_title
+                    }
+                    else if self.ordered_list_marker()?.is_some() {
+                        Self::ordered_list
+                    }
+                    else {
+                        Self::paragraph
+                    }
+                },
+                Star => {
+                    if self.unordered_list_marker()?.is_some() {
+                        Self::unordered_list
+                    }
+                    else {
+                        Self::paragraph
+                    }
+                },
+                Backquote | Caret | CloseCurlyBracket | CloseDoubleQuote | CloseSingleQuote | CloseSquareBracket |
+                    DelimiterLine(..) | DoubleBackquote | DoubleColon | DoubleGt | DoubleLt | DoubleStar |
+                    DoubleUnderscore | NumberSign | OpenCurlyBracket | OpenDoubleQuote | OpenSingleQuote | Plus |
+                    Tilde | TriplePlus | Underscore | Url(_) =>
                     Self::paragraph,
+                Word(_) => {
+                    if self.is_toc_macro()? {
+                        Self::toc_macro
+                    }
+                    else if self.is_block_image()? {
+                        Self::block_image
+                    }
+                    else if self.unordered_list_marker()?.is_some() {
+                        Self::unordered_list
+                    }
+                    else if self.ordered_list_marker()?.is_some() {
+                        Self::ordered_list
+                    }
+                    else {
+                        Self::paragraph
+                    }
+                },
+            };
+        let node = func(self);
+        self.header_done = true;
+        node
+    }
+
+    /// Parse an open block delimited by `--`.
+    fn open_block(&mut self) -> Result<Node> {
+        let pos = self.tokens.pos();
+        let delimiter = self.tokens.token()?;
+        Ok(Open(self.delimited_nodes(&delimiter, NodeKind::Open, pos)?, pos))
+    }
+
+    /// Parse an ordered list, like `. item` or `1. item`.
+    /// The following items with a deeper marker, like `.. item`, are in a list nested in the
+    /// previous item.
+    fn ordered_list(&mut self) -> Result<Node> {
+        let pos = self.tokens.pos();
+        let (level, start) =
+            match self.ordered_list_marker()? {
+                Some(marker) => marker,
+                None => return Err(self.unexpected_token(".")),
+            };
+        let mut items = vec![];
+        while let Some((item_level, _)) = self.ordered_list_marker()? {
+            if item_level < level {
+                break;
+            }
+            self.tokens.token()?;
+            self.eat(Space)?;
+            let text = self.list_item_text()?;
+            let nested =
+                match self.ordered_list_marker()? {
+                    Some((next_level, _)) if next_level > level => Some(self.ordered_list()?),
+                    _ => None,
+                };
+            items.push(ListItem {
+                nested,
+                text,
+            });
+        }
+        Ok(OrderedList {
+            attributes: vec![],
+            items,
+            level,
+            pos,
+            start,
+        })
+    }
+
+    /// Get the level and the explicit number of the ordered list item starting at the next
+    /// token, like `(2, None)` for `.. item` or `(1, Some(3))` for `3. item`.
+    fn ordered_list_marker(&mut self) -> Result<Option<(usize, Option<usize>)>> {
+        let marker =
+            match self.tokens.peek() {
+                Ok(&DotRun(count)) => (count, None),
+                Ok(Word(word)) => {
+                    let number = word.strip_suffix(b".")
+                        .filter(|number| !number.is_empty() && number.iter().all(u8::is_ascii_digit))
+                        .and_then(|number| String::from_utf8_lossy(number).parse().ok());
+                    match number {
+                        Some(number) => (1, Some(number)),
+                        None => return Ok(None),
+                    }
+                },
+                Ok(_) | Err(Error::Eof) => return Ok(None),
+                Err(error) => return Err(error),
             };
-        func(self)
+        if matches!(self.tokens.peek_nth(1), Ok(&Space)) {
+            Ok(Some(marker))
+        }
+        else {
+            Ok(None)
+        }
     }
 
     /// Parse a page break
     fn page_break(&mut self) -> Result<Node> {
+        let pos = self.tokens.pos();
         self.eat(TripleLt)?;
-        Ok(PageBreak)
+        Ok(PageBreak(pos))
     }
 
-    /// Parse a paragraph.
+    /// Parse a paragraph, which can be an admonition paragraph like `NOTE: text`.
     fn paragraph(&mut self) -> Result<Node> {
-        let mut items = vec![];
+        if self.is_description_list_item()? {
+            return self.description_list();
+        }
+        let pos = self.tokens.pos();
+        if let Some(kind) = self.admonition_kind()? {
+            self.tokens.token()?;
+            self.eat(Colon)?;
+            self.eat(Space)?;
+            let text = self.paragraph_text(vec![], false)?;
+            return Ok(Admonition {
+                caption: self.admonition_caption(kind),
+                kind,
+                pos,
+                text,
+            });
+        }
+        self.paragraph_starting_with(vec![], pos)
+    }
+
+    /// Parse a paragraph starting at the position `pos`, whose first items were already parsed.
+    fn paragraph_starting_with(&mut self, items: Vec<Item>, pos: Pos) -> Result<Node> {
+        let text = self.paragraph_text(items, false)?;
+        Ok(Paragraph(text, vec![], pos))
+    }
+
+    /// Parse the text of a paragraph whose first items were already parsed.
+    /// With `hardbreaks`, the lines are separated by line breaks and a single empty line is kept
+    /// as a line break instead of ending the paragraph, like in a `[verse]` block.
+    fn paragraph_text(&mut self, mut items: Vec<Item>, hardbreaks: bool) -> Result<Text> {
         loop {
             let mut line = self.text_while(|node| node != &NewLine)?;
-            // End of paragraph on an empty line.
-            if line.items.is_empty() {
+            let has_line_break = line.items.last() == Some(&Item::LineBreak);
+            items.append(&mut line.items);
+            match self.tokens.token() {
+                Ok(_) => (),
+                Err(Error::Eof) => break,
+                Err(error) => return Err(error),
+            }
+            if hardbreaks && self.is_single_empty_line()? {
+                self.eat(NewLine)?;
+                items.push(Item::LineBreak);
+            }
+            else if self.is_paragraph_end()? {
                 break;
             }
-            items.append(&mut line.items);
+            if !has_line_break {
+                items.push(if hardbreaks { Item::LineBreak } else { Item::Space });
+            }
+        }
+        Ok(Text::new(items))
+    }
+
+    /// Parse a passthrough block delimited by `++++`, whose content is kept as is.
+    fn passthrough_block(&mut self) -> Result<Node> {
+        let pos = self.tokens.pos();
+        let delimiter = self.tokens.token()?.to_string();
+        self.eat_line_end()?;
+        let lines = self.delimited_lines(delimiter.as_bytes(), NodeKind::Passthrough, pos)?;
+        Ok(Passthrough(lines.join("\n"), pos))
+    }
+
+    /// Parse a quote block delimited by `____`, without attribution.
+    fn quote_block(&mut self) -> Result<Node> {
+        self.quote_with_attribution(None, None)
+    }
+
+    /// Parse a quote block, which is delimited by `____` or is the next paragraph, with the
+    /// `author` and the `source` of its attribution, like `[quote, Author, Source]`.
+    fn quote_with_attribution(&mut self, author: Option<String>, source: Option<String>) -> Result<Node> {
+        let pos = self.tokens.pos();
+        let content =
+            if let DelimiterLine(b'_', count) = *self.tokens.peek()? {
+                self.tokens.token()?;
+                self.delimited_nodes(&DelimiterLine(b'_', count), NodeKind::Quote, pos)?
+            }
+            else {
+                vec![self.paragraph_starting_with(vec![], pos)?]
+            };
+        Ok(Quote {
+            author,
+            content,
+            pos,
+            source,
+        })
+    }
+
+    /// Parse a section title.
+    fn section(&mut self) -> Result<Node> {
+        let pos = self.tokens.pos();
+        let equals = self.tokens.token()?;
+        let level =
+            match equals {
+                EqualsRun(count) => count - 1,
+                _ => return Err(self.unexpected_token("=")),
+            };
+        // The `leveloffset` attribute, set by the includes, shifts the levels of the sections.
+        let level = (level as isize + self.level_offset()).max(0) as usize;
+        if *self.tokens.peek()? != Space || level > MAX_SECTION_LEVEL {
+            // Not a section title, since it is not followed by a space or it is too deep.
+            return self.paragraph_starting_with(vec![Item::Word(equals.to_string())], pos);
+        }
+        self.eat(Space)?;
+        let title = self.line_text()?;
+        Ok(Section {
+            attributes: vec![],
+            level,
+            pos,
+            title,
+        })
+    }
+
+    /// Parse an inline passthrough, like `+++<br>+++`, whose content is kept as is.
+    fn passthrough(&mut self, attributes: Vec<Attribute>) -> Result<Item> {
+        if !self.is_closed(&TriplePlus)? {
+            return self.literal(attributes);
+        }
+        self.eat(TriplePlus)?;
+        let mut tokens = vec![];
+        while *self.tokens.peek()? != TriplePlus {
+            tokens.push(self.tokens.token()?);
+        }
+        self.eat(TriplePlus)?;
+        Ok(Item::Passthrough(tokens_text(&tokens)))
+    }
+
+    /// Parse text between curved quotes, like ``"`text`"`` or ``'`text`'``.
+    fn quoted(&mut self, attributes: Vec<Attribute>) -> Result<Item> {
+        let (open, close) =
+            match *self.tokens.peek()? {
+                OpenDoubleQuote => (OpenDoubleQuote, CloseDoubleQuote),
+                _ => (OpenSingleQuote, CloseSingleQuote),
+            };
+        if !self.is_closed(&close)? {
+            return self.literal(attributes);
+        }
+        let double = open == OpenDoubleQuote;
+        self.eat(open)?;
+        let text = self.text_while(|token| token != &close)?;
+        self.eat(close)?;
+        Ok(Item::Quoted { double, text })
+    }
+
+    /// Parse a table delimited by `|===`, with one row on each line, like `|cell 1 |cell 2`.
+    fn table(&mut self) -> Result<Node> {
+        let pos = self.tokens.pos();
+        self.table_delimiter()?;
+        let mut rows = vec![];
+        loop {
+            match self.tokens.peek() {
+                Ok(&NewLine) => {
+                    self.tokens.token()?;
+                },
+                Ok(&Pipe) => {
+                    if self.is_table_delimiter()? {
+                        self.table_delimiter()?;
+                        break;
+                    }
+                    rows.push(self.table_row()?);
+                },
+                Ok(_) => return Err(self.unexpected_token("|")),
+                Err(Error::Eof) => return Err(Error::UnterminatedBlock { kind: NodeKind::Table, pos }),
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(Table {
+            attributes: vec![],
+            pos,
+            rows,
+        })
+    }
+
+    /// Parse a table delimiter line, `|===`.
+    fn table_delimiter(&mut self) -> Result<()> {
+        self.eat(Pipe)?;
+        self.tokens.token()?;
+        match self.tokens.token() {
+            Ok(NewLine) | Err(Error::Eof) => Ok(()),
+            Ok(_) => Err(self.unexpected_token("newline")),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Parse the cells of a table row.
+    fn table_row(&mut self) -> Result<Vec<Text>> {
+        let mut cells = vec![];
+        while self.is_next(&Pipe)? {
+            self.eat(Pipe)?;
+            if self.is_next(&Space)? {
+                self.eat(Space)?;
+            }
+            let mut cell = self.text_while(|token| token != &Pipe && token != &NewLine)?;
+            while let Some(&Item::Space) = cell.items.last() {
+                cell.items.pop();
+            }
+            cells.push(cell);
         }
-        Ok(Paragraph(Text::new(items)))
+        self.eat_line_end()?;
+        Ok(cells)
     }
 
-    /// Parse a space.
+    /// Parse a space, or a hard line break, ` +` at the end of a line.
     fn space(&mut self, _attributes: Vec<Attribute>) -> Result<Item> {
+        if self.is_line_break()? {
+            self.eat(Space)?;
+            self.eat(Plus)?;
+            return Ok(Item::LineBreak);
+        }
         self.eat(Space)?;
         Ok(Item::Space)
     }
 
-    /// Parse a text item.
-    fn text_item(&mut self, mut attributes: Vec<Attribute>) -> Result<Item> {
-        if *self.tokens.peek()? == OpenSquareBracket {
-            if !attributes.is_empty() {
-                return Err(self.unexpected_token("["));
-            }
-            attributes = self.attributes()?;
+    /// Skip the next spaces.
+    fn spaces(&mut self) -> Result<()> {
+        while matches!(self.tokens.peek(), Ok(&Space)) {
+            self.eat(Space)?;
         }
+        Ok(())
+    }
+
+    /// Parse a text item.
+    fn text_item(&mut self, attributes: Vec<Attribute>) -> Result<Item> {
         let func =
             match *self.tokens.peek()? {
-                Backquote => Self::inline_code,
+                Backquote => Self::backquote,
                 Caret => Self::superscript,
+                CloseCurlyBracket | CloseDoubleQuote | CloseSingleQuote | CloseSquareBracket | Colon |
+                    DelimiterLine(..) | DoubleColon | DoubleGt | Pipe | Plus => Self::literal,
                 DoubleBackquote => Self::unconstrained_inline_code,
+                DoubleLt => Self::cross_reference,
                 DoubleStar => Self::unconstrained_bold,
                 DoubleUnderscore => Self::unconstrained_italic,
+                DotRun(_) | EqualsRun(_) => Self::literal,
                 NumberSign => Self::mark,
-                OpenSquareBracket => Self::text_item,
+                OpenCurlyBracket => Self::attribute_reference,
+                OpenDoubleQuote | OpenSingleQuote => Self::quoted,
+                OpenSquareBracket => Self::attributes_or_brackets,
                 Space => Self::space,
                 Star => Self::bold,
                 Tilde => Self::subscript,
+                TriplePlus => Self::passthrough,
                 Underscore => Self::italic,
+                Url(_) => Self::link,
                 Word(_) => Self::word,
                 ref node => return Err(Error::Msg(format!("Should have got text token, but got {:?}", node))), // TODO: better error.
             };
@@ -196,12 +1646,41 @@ impl<R: BufRead> Parser<R> {
         Ok(item)
     }
 
+    /// Parse the `content` of a verbatim block with the normal substitutions, like the formatted
+    /// text and the attribute references, while keeping its lines.
+    fn substituted_text(&self, content: &str) -> Result<Text> {
+        let content = format!("{}\n", content);
+        let mut parser = Parser::new(Lexer::from_bytes(content.as_bytes()));
+        parser.attributes = self.attributes.clone();
+        let mut items = vec![];
+        loop {
+            match parser.tokens.peek() {
+                Ok(_) => (),
+                Err(Error::Eof) => break,
+                Err(error) => return Err(error),
+            }
+            if !items.is_empty() {
+                items.push(Item::Word("\n".to_string()));
+            }
+            items.append(&mut parser.text_while(|token| token != &NewLine)?.items);
+            parser.eat(NewLine)?;
+        }
+        Ok(Text::new(items))
+    }
+
     /// Parse text while the predicate returns true.
     fn text_while<F: Fn(&Token) -> bool>(&mut self, predicate: F) -> Result<Text> {
         let mut items = vec![];
+        self.after_word = false;
         loop {
             let is_newline = {
-                let token = self.tokens.peek()?;
+                let token =
+                    match self.tokens.peek() {
+                        Ok(token) => token,
+                        // The end of the document ends the text too.
+                        Err(Error::Eof) => break,
+                        Err(error) => return Err(error),
+                    };
                 if !predicate(token) {
                     break;
                 }
@@ -209,14 +1688,160 @@ impl<R: BufRead> Parser<R> {
             };
             if is_newline {
                 self.eat(NewLine)?;
+                self.after_word = false;
                 continue;
             }
             let item = self.text_item(vec![])?;
+            self.after_word =
+                match item {
+                    Item::Word(ref word) => word.chars().last().is_some_and(char::is_alphanumeric),
+                    _ => false,
+                };
             items.push(item);
         }
         Ok(Text::new(items))
     }
 
+    /// Parse the table of contents macro, `toc::[]`.
+    fn toc_macro(&mut self) -> Result<Node> {
+        let pos = self.tokens.pos();
+        self.tokens.token()?;
+        self.eat(DoubleColon)?;
+        self.eat(OpenSquareBracket)?;
+        self.eat(CloseSquareBracket)?;
+        Ok(Toc(pos))
+    }
+
+    /// Parse an unordered list, whose items start with the same marker, like `* item` or
+    /// `- item`. The list ends on an empty line.
+    fn unordered_list(&mut self) -> Result<Node> {
+        let pos = self.tokens.pos();
+        let marker = self.unordered_list_marker()?;
+        let mut items = vec![];
+        while self.unordered_list_marker()? == marker {
+            self.tokens.token()?;
+            self.eat(Space)?;
+            let text = self.list_item_text()?;
+            items.push(ListItem {
+                nested: None,
+                text,
+            });
+        }
+        Ok(UnorderedList(items, vec![], pos))
+    }
+
+    /// Parse a link to a URL, like `https://example.com`.
+    fn link(&mut self, _attributes: Vec<Attribute>) -> Result<Item> {
+        let target =
+            match self.tokens.token()? {
+                Url(target) => target,
+                _ => return Err(self.unexpected_token("URL")),
+            };
+        let mut attributes = vec![];
+        let mut text = None;
+        let is_empty = matches!(self.tokens.peek_nth(1), Ok(&CloseSquareBracket));
+        if matches!(self.tokens.peek(), Ok(&OpenSquareBracket)) && (is_empty || self.is_closed(&CloseSquareBracket)?) {
+            self.eat(OpenSquareBracket)?;
+            let mut tokens = vec![];
+            while *self.tokens.peek()? != CloseSquareBracket {
+                tokens.push(self.tokens.token()?);
+            }
+            self.eat(CloseSquareBracket)?;
+            // The text is an attribute list only when it contains named attributes, like
+            // `Example,window=_blank`.
+            let list = tokens_text(&tokens);
+            let content =
+                if list.contains('=') {
+                    let mut values = split_attribute_list(&list).into_iter();
+                    let content = values.next().unwrap_or_default().trim();
+                    for value in values {
+                        attributes.extend(parse_attribute(value.trim(), false));
+                    }
+                    if content.contains('=') {
+                        attributes.extend(parse_attribute(content, false));
+                        ""
+                    }
+                    else {
+                        content.trim_matches(['"', '\''])
+                    }
+                }
+                else {
+                    list.as_str()
+                };
+            if !content.is_empty() {
+                text = Some(self.substituted_text(content)?);
+            }
+        }
+        Ok(Item::Link {
+            attributes,
+            target,
+            text,
+        })
+    }
+
+    /// Get the marker of the unordered list item starting at the next token, like `*` in `* item`
+    /// or `-` in `- item`.
+    fn unordered_list_marker(&mut self) -> Result<Option<u8>> {
+        let marker =
+            match self.tokens.peek() {
+                Ok(&Star) => b'*',
+                Ok(Word(word)) if word == b"-" => b'-',
+                Ok(_) | Err(Error::Eof) => return Ok(None),
+                Err(error) => return Err(error),
+            };
+        if matches!(self.tokens.peek_nth(1), Ok(&Space)) {
+            Ok(Some(marker))
+        }
+        else {
+            Ok(None)
+        }
+    }
+
+    /// Parse a block whose content is kept as is, because of its `style`, like `[literal]` or
+    /// `[source]`. This is either a delimited block, like `----`, or a paragraph.
+    fn verbatim_block(&mut self, style: &str) -> Result<Node> {
+        let pos = self.tokens.pos();
+        let first_line =
+            match self.tokens.raw_line() {
+                Ok(line) => line,
+                Err(Error::Eof) => vec![],
+                Err(error) => return Err(error),
+            };
+        let is_delimiter = first_line.len() >= 4 &&
+            (first_line.iter().all(|&byte| byte == b'-') || first_line.iter().all(|&byte| byte == b'.'));
+        let mut lines =
+            if is_delimiter {
+                let kind = if style == "literal" { NodeKind::Literal } else { NodeKind::Listing };
+                self.delimited_lines(&first_line, kind, pos)?
+            }
+            else {
+                let mut lines = vec![String::from_utf8(first_line)?];
+                loop {
+                    match self.tokens.raw_line() {
+                        Ok(ref line) if line.iter().all(u8::is_ascii_whitespace) => break,
+                        Ok(line) => lines.push(String::from_utf8(line)?),
+                        Err(Error::Eof) => break,
+                        Err(error) => return Err(error),
+                    }
+                }
+                lines
+            };
+        strip_indentation(&mut lines, 0);
+        let content = lines.join("\n");
+        if style != "literal" {
+            Ok(Listing {
+                content,
+                language: None,
+                pos,
+                source: style == "source",
+                title: self.block_title.take(),
+            })
+        }
+        else {
+            Ok(Literal(content, pos))
+        }
+    }
+
     /// Return an UnexpectedToken error.
     fn unexpected_token(&mut self, expected: &str) -> Error {
         let actual = self.tokens.peek()
@@ -229,13 +1854,317 @@ impl<R: BufRead> Parser<R> {
         }
     }
 
+    /// Return an UnexpectedToken error for the `actual` token of a list of attributes, which were
+    /// already read from the lexer. The end of the list is the closing square bracket.
+    fn unexpected_attribute_token(&mut self, actual: Option<Token>, expected: &str) -> Error {
+        Error::UnexpectedToken {
+            actual: actual.unwrap_or(CloseSquareBracket).to_string(),
+            expected: expected.to_string(),
+            pos: self.tokens.pos(),
+        }
+    }
+
+    /// Parse a sidebar block delimited by `****`, which contains other blocks.
+    fn sidebar_block(&mut self) -> Result<Node> {
+        let title = self.block_title.take();
+        let pos = self.tokens.pos();
+        let delimiter = self.tokens.token()?;
+        Ok(Sidebar {
+            nodes: self.delimited_nodes(&delimiter, NodeKind::Sidebar, pos)?,
+            pos,
+            title,
+        })
+    }
+
     /// Parse a single word.
     fn word(&mut self, _attributes: Vec<Attribute>) -> Result<Item> {
         if let Ok(Word(bytes)) = self.tokens.token() {
+            if bytes == b"footnote" && matches!(self.tokens.peek(), Ok(&Colon)) &&
+                matches!(self.tokens.peek_nth(1), Ok(&OpenSquareBracket))
+            {
+                return self.footnote();
+            }
+            if bytes == b"image" && matches!(self.tokens.peek(), Ok(&Colon)) && self.macro_end(1)?.is_some() {
+                return self.inline_image();
+            }
+            if bytes == b"icon" && matches!(self.tokens.peek(), Ok(&Colon)) && self.macro_end(1)?.is_some() {
+                return self.icon();
+            }
+            if bytes == b"anchor" && matches!(self.tokens.peek(), Ok(&Colon)) && self.macro_end(1)?.is_some() {
+                return self.anchor_macro();
+            }
+            if bytes == b"btn" && matches!(self.tokens.peek(), Ok(&Colon)) &&
+                matches!(self.tokens.peek_nth(1), Ok(&OpenSquareBracket))
+            {
+                return self.button();
+            }
+            if bytes == b"kbd" && matches!(self.tokens.peek(), Ok(&Colon)) &&
+                matches!(self.tokens.peek_nth(1), Ok(&OpenSquareBracket))
+            {
+                return self.keyboard();
+            }
+            if bytes == b"menu" && matches!(self.tokens.peek(), Ok(&Colon)) && self.macro_end(1)?.is_some() {
+                return self.menu();
+            }
             Ok(Item::Word(String::from_utf8(bytes)?))
         }
         else {
-            return Err(Error::Msg("Should have got word token".to_string())); // TODO: better error.
+            Err(Error::Msg("Should have got word token".to_string())) // TODO: better error.
+        }
+    }
+}
+
+impl<'a> Parser<&'a [u8]> {
+    /// Create a parser for the `bytes` of a document starting at the byte `offset`, which must be
+    /// at the start of a block, like after an edit in an editor.
+    /// The document attributes are the ones of this parser, which should have parsed the document
+    /// up to `offset`. The positions of the nodes are still relative to the whole document.
+    pub fn reparse_from(&self, bytes: &'a [u8], offset: usize) -> Self {
+        let (before, rest) = bytes.split_at(offset);
+        let line = before.iter().filter(|&&byte| byte == b'\n').count() + 1;
+        let line_start = before.iter().rposition(|&byte| byte == b'\n').map_or(0, |index| index + 1);
+        let pos = Pos::new(line, offset - line_start + 1);
+        Parser {
+            after_word: false,
+            attributes: self.attributes.clone(),
+            block_title: None,
+            header_done: self.header_done || offset > 0,
+            single_line: false,
+            tokens: Lexer::with_position(rest, pos),
+        }
+    }
+}
+
+/// Get the name of the file of an image `target` without its extension, like `sunset` for
+/// `images/sunset.png`, which is the default alternative text of the image.
+fn image_stem(target: &str) -> &str {
+    let name = target.rsplit('/').next().unwrap_or(target);
+    match name.rfind('.') {
+        Some(index) if index > 0 => &name[..index],
+        _ => name,
+    }
+}
+
+/// Check if the `name` of an attribute entry is valid, like `my_attr-2`, or `!name` and `name!` to
+/// unset the attribute.
+fn is_attribute_name(name: &str) -> bool {
+    let name = name.strip_prefix('!').or_else(|| name.strip_suffix('!')).unwrap_or(name);
+    let mut characters = name.chars();
+    characters.next().is_some_and(|character| character.is_ascii_alphanumeric() || character == '_') &&
+        characters.all(|character| character.is_ascii_alphanumeric() || character == '_' || character == '-')
+}
+
+/// Check if the token starts formatted text, to which attributes can be applied.
+fn is_formatting_mark(token: &Token) -> bool {
+    matches!(*token, Backquote | Caret | DoubleBackquote | DoubleStar | DoubleUnderscore | NumberSign | Star |
+        Tilde | Underscore)
+}
+
+/// Parse an author, like `Jane Q Doe <jane@example.com>`.
+fn parse_author(text: &str) -> Author {
+    let text = text.trim();
+    let (name, email) =
+        match (text.find('<'), text.rfind('>')) {
+            (Some(start), Some(end)) if start < end =>
+                (text[..start].trim(), Some(text[start + 1..end].trim().to_string())),
+            _ => (text, None),
+        };
+    let names: Vec<_> = name.split_whitespace().collect();
+    let (lastname, middlename) =
+        match names.len() {
+            0 | 1 => (None, None),
+            2 => (Some(names[1].to_string()), None),
+            count => (Some(names[count - 1].to_string()), Some(names[1..count - 1].join(" "))),
+        };
+    Author {
+        email,
+        firstname: names.first().map(ToString::to_string).unwrap_or_default(),
+        lastname,
+        middlename,
+        name: names.join(" "),
+    }
+}
+
+/// Parse the author line of the document header, where the authors are separated by `;`, like
+/// `Jane Doe <jane@example.com>; John Smith`.
+fn parse_authors(line: &str) -> Vec<Author> {
+    line.split(';')
+        .filter(|author| !author.trim().is_empty())
+        .map(parse_author)
+        .collect()
+}
+
+/// Parse the revision line of the document header, like `v1.0, 2024-01-01: remark`.
+/// The partial forms, like only the date, are supported.
+fn parse_revision(line: &str) -> Revision {
+    let (line, remark) =
+        match line.find(':') {
+            Some(index) => (&line[..index], Some(line[index + 1..].trim().to_string())),
+            None => (line, None),
+        };
+    let (number, date) =
+        match line.find(',') {
+            Some(index) => (Some(&line[..index]), Some(line[index + 1..].trim())),
+            None if is_revision_number(line.trim()) => (Some(line), None),
+            None => (None, Some(line.trim())),
+        };
+    Revision {
+        date: date.filter(|date| !date.is_empty()).map(ToString::to_string),
+        number: number.map(|number| number.trim().trim_start_matches('v').to_string()),
+        remark: remark.filter(|remark| !remark.is_empty()),
+    }
+}
+
+/// Re-indent the content of a listing block to the `indent` attribute, like `[indent=2]`.
+fn indent_listing(content: String, attributes: &[Attribute]) -> String {
+    match named_attribute(attributes, "indent").and_then(|indent| indent.parse().ok()) {
+        Some(indent) => {
+            let mut lines: Vec<_> = content.split('\n').map(ToString::to_string).collect();
+            strip_indentation(&mut lines, indent);
+            lines.join("\n")
+        },
+        None => content,
+    }
+}
+
+/// Check if the text is a revision number, like `v1.0`.
+fn is_revision_number(text: &str) -> bool {
+    matches!(text.strip_prefix('v'), Some(number) if number.starts_with(|character: char| character.is_ascii_digit()))
+}
+
+/// Parse a value of an attribute list.
+/// The first value is the role (or the style of a block), like in `[source,rust]`.
+fn parse_attribute(value: &str, first: bool) -> Vec<Attribute> {
+    if let Some((name, value)) = value.split_once('=') {
+        let value = value.trim_matches(['"', '\'']);
+        match name {
+            "id" => vec![Id(value.to_string())],
+            "opts" | "options" =>
+                value.split(',')
+                    .filter(|option| !option.is_empty())
+                    .map(|option| Attribute::Option(option.trim().to_string()))
+                    .collect(),
+            _ => vec![Attribute::Named {
+                name: name.to_string(),
+                value: value.to_string(),
+            }],
+        }
+    }
+    else if value.starts_with(['#', '.', '%']) {
+        parse_shorthand(value)
+    }
+    else if first {
+        vec![Role(value.to_string())]
+    }
+    else {
+        vec![Attribute::Positional(value.to_string())]
+    }
+}
+
+/// Parse the shorthand of an id, roles and options, like `#intro.lead%header`.
+fn parse_shorthand(value: &str) -> Vec<Attribute> {
+    let mut attributes = vec![];
+    let mut rest = value;
+    while let Some(marker) = rest.chars().next() {
+        let end = rest[1..].find(['#', '.', '%']).map_or(rest.len(), |index| index + 1);
+        let name = rest[1..end].to_string();
+        if !name.is_empty() {
+            attributes.push(
+                match marker {
+                    '#' => Id(name),
+                    '.' => Role(name),
+                    _ => Attribute::Option(name),
+                });
+        }
+        rest = &rest[end..];
+    }
+    attributes
+}
+
+/// Parse the keys of a keyboard macro, like `Ctrl+Alt+Del`.
+/// A `+` is part of the key, instead of a separator, when it is escaped, like `\+`, or when
+/// the key is empty, like in `Ctrl++`.
+fn parse_keys(text: &str) -> Vec<String> {
+    let mut keys = vec![];
+    let mut key = String::new();
+    let mut characters = text.chars().peekable();
+    while let Some(character) = characters.next() {
+        match character {
+            '\\' if characters.peek() == Some(&'+') => {
+                key.push('+');
+                characters.next();
+            },
+            '+' if !key.trim().is_empty() => keys.push(key.split_off(0).trim().to_string()),
+            character => key.push(character),
         }
     }
+    if !key.trim().is_empty() {
+        keys.push(key.trim().to_string());
+    }
+    keys
+}
+
+/// Get the language of a source block from its attributes, like `[source,rust]`.
+fn source_language(attributes: &[Attribute]) -> Option<String> {
+    match attributes {
+        [Role(style), Attribute::Positional(language), ..] if style == "source" && !language.is_empty() =>
+            Some(language.clone()),
+        _ => None,
+    }
+}
+
+/// Split an attribute list on the commas which are not between quotes. A double or single
+/// quote only starts a quoted value when it is the first character of the value, after the
+/// spaces, so that an apostrophe like in `O'Brien` is kept as is.
+fn split_attribute_list(list: &str) -> Vec<&str> {
+    let mut values = vec![];
+    let mut quote = None;
+    let mut start = 0;
+    let mut value_start = true;
+    for (index, character) in list.char_indices() {
+        match character {
+            _ if quote == Some(character) => quote = None,
+            _ if quote.is_some() => (),
+            '"' | '\'' if value_start => quote = Some(character),
+            ',' => {
+                values.push(&list[start..index]);
+                start = index + 1;
+            },
+            _ => (),
+        }
+        value_start = quote.is_none() && (character == ',' || character == '=' || (value_start && character == ' '));
+    }
+    values.push(&list[start..]);
+    values
+}
+
+/// Replace the common leading indentation of the non-blank lines by `indent` spaces.
+fn strip_indentation(lines: &mut [String], indent: usize) {
+    let common = lines.iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start_matches(' ').len())
+        .min()
+        .unwrap_or(0);
+    for line in lines.iter_mut() {
+        if !line.trim().is_empty() {
+            *line = format!("{:indent$}{}", "", &line[common..], indent = indent);
+        }
+    }
+}
+
+/// Get the value of the named attribute `name`, like `start` in `[start=3]`.
+fn named_attribute<'a>(attributes: &'a [Attribute], name: &str) -> Option<&'a str> {
+    attributes.iter()
+        .rev()
+        .find_map(|attribute| match *attribute {
+            Attribute::Named { name: ref attribute_name, ref value } if attribute_name == name => Some(value.as_str()),
+            _ => None,
+        })
+}
+
+/// Get the text of the tokens as written in the document.
+fn tokens_text(tokens: &[Token]) -> String {
+    tokens.iter()
+        .map(Token::text)
+        .collect()
 }
@@ -22,7 +22,7 @@
 //! Position information for a token or a node.
 
 /// Position as line and column.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Pos {
     pub column: usize,
     pub line: usize,
@@ -21,6 +21,10 @@
 
 //! Tokens from an asciidoctor document.
 
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
 use self::Token::*;
 
 /// Different types of token.
@@ -28,43 +32,92 @@ use self::Token::*;
 pub enum Token {
     Backquote,
     Caret,
+    CloseCurlyBracket,
+    /// The end of a double curved quote, `` `" ``.
+    CloseDoubleQuote,
+    /// The end of a single curved quote, `` `' ``.
+    CloseSingleQuote,
     CloseSquareBracket,
+    Colon,
+    DelimiterLine(u8, usize),
+    DotRun(usize),
     DoubleBackquote,
+    DoubleColon,
+    DoubleGt,
+    DoubleLt,
     DoubleStar,
     DoubleUnderscore,
+    EqualsRun(usize),
     NewLine,
     NumberSign,
+    OpenCurlyBracket,
+    /// The start of a double curved quote, `` "` ``.
+    OpenDoubleQuote,
+    /// The start of a single curved quote, `` '` ``.
+    OpenSingleQuote,
     OpenSquareBracket,
+    Pipe,
+    Plus,
     Space,
     Star,
     Tilde,
     TripleApos,
     TripleLt,
+    TriplePlus,
     Underscore,
+    Url(String),
     Word(Vec<u8>),
 }
 
 impl Token {
-    /// Convert the token to a user-readable string.
-    /// Useful for error reporting.
-    pub fn to_string(&self) -> String {
+    /// Get the text of the token as written in the document.
+    pub fn text(&self) -> String {
+        match *self {
+            NewLine => "\n".to_string(),
+            Space => " ".to_string(),
+            _ => self.to_string(),
+        }
+    }
+}
+
+/// Convert the token to a user-readable string.
+/// Useful for error reporting.
+impl Display for Token {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
         match *self {
-            Backquote => "`".to_string(),
-            Caret => "^".to_string(),
-            CloseSquareBracket => "]".to_string(),
-            DoubleBackquote => "``".to_string(),
-            DoubleStar => "**".to_string(),
-            DoubleUnderscore => "__".to_string(),
-            NewLine => "(newline)".to_string(),
-            NumberSign => "#".to_string(),
-            OpenSquareBracket => "[".to_string(),
-            Space => "(space)".to_string(),
-            Star => "*".to_string(),
-            Tilde => "~".to_string(),
-            TripleApos => "'''".to_string(),
-            TripleLt => "<<<".to_string(),
-            Underscore => "_".to_string(),
-            Word(ref word) => String::from_utf8_lossy(word).to_string(),
+            Backquote => write!(fmt, "`"),
+            Caret => write!(fmt, "^"),
+            CloseCurlyBracket => write!(fmt, "}}"),
+            CloseDoubleQuote => write!(fmt, "`\""),
+            CloseSingleQuote => write!(fmt, "`'"),
+            CloseSquareBracket => write!(fmt, "]"),
+            Colon => write!(fmt, ":"),
+            DelimiterLine(delimiter, count) => write!(fmt, "{}", (delimiter as char).to_string().repeat(count)),
+            DotRun(count) => write!(fmt, "{}", ".".repeat(count)),
+            DoubleBackquote => write!(fmt, "``"),
+            DoubleColon => write!(fmt, "::"),
+            DoubleGt => write!(fmt, ">>"),
+            DoubleLt => write!(fmt, "<<"),
+            DoubleStar => write!(fmt, "**"),
+            DoubleUnderscore => write!(fmt, "__"),
+            EqualsRun(count) => write!(fmt, "{}", "=".repeat(count)),
+            NewLine => write!(fmt, "(newline)"),
+            NumberSign => write!(fmt, "#"),
+            OpenCurlyBracket => write!(fmt, "{{"),
+            OpenDoubleQuote => write!(fmt, "\"`"),
+            OpenSingleQuote => write!(fmt, "'`"),
+            OpenSquareBracket => write!(fmt, "["),
+            Pipe => write!(fmt, "|"),
+            Plus => write!(fmt, "+"),
+            Space => write!(fmt, "(space)"),
+            Star => write!(fmt, "*"),
+            Tilde => write!(fmt, "~"),
+            TripleApos => write!(fmt, "'''"),
+            TripleLt => write!(fmt, "<<<"),
+            TriplePlus => write!(fmt, "+++"),
+            Underscore => write!(fmt, "_"),
+            Url(ref url) => write!(fmt, "{}", url),
+            Word(ref word) => write!(fmt, "{}", String::from_utf8_lossy(word)),
         }
     }
 }
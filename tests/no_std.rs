@@ -0,0 +1,45 @@
+/*
+ * Copyright (c) 2017 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Only use `core` and `alloc`, like a `no_std` user of the crate would.
+
+#![no_std]
+
+extern crate asciidoctor;
+
+use asciidoctor::{Error, Lexer, Parser, StringSink};
+use asciidoctor::html::{self, Generator};
+
+#[test]
+fn test_parse_bytes() {
+    let input: &[u8] = b"*chunky* bacon\n\n'''\n";
+    let mut parser = Parser::new(Lexer::from_bytes(input));
+    let mut sink = StringSink::new();
+    let mut generator = Generator::new();
+    loop {
+        match parser.node() {
+            Ok(node) => html::gen(&mut generator, &node, &mut sink).unwrap(),
+            Err(Error::Eof) => break,
+            Err(error) => panic!("cannot parse asciidoctor: {}", error),
+        }
+    }
+    assert_eq!(sink.as_str(), "<div class=\"paragraph\"><p><strong>chunky</strong> bacon</p></div><hr/>");
+}